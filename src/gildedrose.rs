@@ -1,9 +1,159 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt::{self, Display};
+use std::iter::FromIterator;
+use std::sync::Arc;
 
+pub mod constants;
+mod csv;
+mod format;
+#[cfg(feature = "serde")]
+mod jsonl;
+#[cfg(feature = "testing")]
+pub mod testing;
+
+pub use self::csv::{CsvError, ParseItemError};
+pub use self::format::{ItemFormat, PipeDelimited};
+#[cfg(feature = "serde")]
+pub use self::jsonl::ImportError;
+
+use self::constants::{
+    BACKSTAGE_TIER2_DAYS, BACKSTAGE_TIER3_DAYS, CONJURED_DECAY_MULTIPLIER,
+    EXPIRED_QUALITY_INCREMENT, LEGENDARY_QUALITY, MAX_QUALITY, MIN_QUALITY, QUALITY_INCREMENT,
+    RIPENING_PEAK_DAY,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ItemKind {
+    AgedBrie,
+    BackstagePasses,
+    Sulfuras,
+    Conjured,
+    Ripening,
+    Normal,
+}
+
+/// Name prefixes that force a kind regardless of the rest of the name.
+const KIND_TAGS: [(&str, ItemKind); 4] = [
+    ("[legendary]", ItemKind::Sulfuras),
+    ("[conjured]", ItemKind::Conjured),
+    ("[brie]", ItemKind::AgedBrie),
+    ("[pass]", ItemKind::BackstagePasses),
+];
+
+impl ItemKind {
+    /// Classifies `name`, ignoring ASCII case. A leading `[legendary]`, `[conjured]`,
+    /// `[brie]` or `[pass]` tag overrides matching on the rest of the name.
+    pub fn from_name(name: &str) -> ItemKind {
+        if let (Some(kind), _) = ItemKind::split_tag(name) {
+            return kind;
+        }
+        let starts_with = |prefix: &str| {
+            name.get(..prefix.len())
+                .is_some_and(|start| start.eq_ignore_ascii_case(prefix))
+        };
+        if name.eq_ignore_ascii_case("aged brie") {
+            ItemKind::AgedBrie
+        } else if starts_with("backstage passes") {
+            ItemKind::BackstagePasses
+        } else if starts_with("sulfuras") {
+            ItemKind::Sulfuras
+        } else if starts_with("conjured") {
+            ItemKind::Conjured
+        } else if starts_with("cheese wheel") {
+            ItemKind::Ripening
+        } else {
+            ItemKind::Normal
+        }
+    }
+
+    /// Splits a leading kind tag off `name`, returning the tagged kind and the rest.
+    fn split_tag(name: &str) -> (Option<ItemKind>, &str) {
+        for &(tag, kind) in KIND_TAGS.iter() {
+            if name
+                .get(..tag.len())
+                .is_some_and(|prefix| prefix.eq_ignore_ascii_case(tag))
+            {
+                return (Some(kind), name[tag.len()..].trim_start());
+            }
+        }
+        (None, name)
+    }
+}
+
+/// Equality and hashing take every field into account: besides name, sell_in and
+/// quality also the kind override, price, flags and quantity.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Item {
     pub name: String,
     pub sell_in: i32,
     pub quality: i32,
+    /// Overrides the kind derived from `name`. When `None`, the kind is classified on demand.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub kind: Option<ItemKind>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub price: Option<u32>,
+    /// Frozen items keep their quality and sell_in through updates.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "std::ops::Not::not")
+    )]
+    pub frozen: bool,
+    /// Archived items are kept in the shop but no longer updated.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "std::ops::Not::not")
+    )]
+    pub archived: bool,
+    /// Scales how fast normal and conjured items decay; 2 decays twice as fast, 0 never.
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            default = "default_decay_multiplier",
+            skip_serializing_if = "is_default_decay_multiplier"
+        )
+    )]
+    pub decay_multiplier: i32,
+    /// How many units the stack holds. Quality and sell_in apply to the whole stack.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default = "default_quantity", skip_serializing_if = "is_single")
+    )]
+    pub quantity: u32,
+    /// Lowest quality decay takes a non-legendary item to. Items already below it are not
+    /// raised.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub quality_floor: Option<i32>,
+}
+
+#[cfg(feature = "serde")]
+fn default_decay_multiplier() -> i32 {
+    1
+}
+
+#[cfg(feature = "serde")]
+fn is_default_decay_multiplier(multiplier: &i32) -> bool {
+    *multiplier == 1
+}
+
+#[cfg(feature = "serde")]
+fn default_quantity() -> u32 {
+    1
+}
+
+#[cfg(feature = "serde")]
+fn is_single(quantity: &u32) -> bool {
+    *quantity == 1
 }
 
 impl Item {
@@ -12,23 +162,335 @@ impl Item {
             name: name.into(),
             sell_in,
             quality,
+            kind: None,
+            price: None,
+            frozen: false,
+            archived: false,
+            decay_multiplier: 1,
+            quantity: 1,
+            quality_floor: None,
+        }
+    }
+
+    pub fn with_price(name: impl Into<String>, sell_in: i32, quality: i32, price: u32) -> Item {
+        Item {
+            price: Some(price),
+            ..Item::new(name, sell_in, quality)
+        }
+    }
+
+    pub fn with_quantity(
+        name: impl Into<String>,
+        sell_in: i32,
+        quality: i32,
+        quantity: u32,
+    ) -> Item {
+        Item {
+            quantity,
+            ..Item::new(name, sell_in, quality)
+        }
+    }
+
+    pub fn with_floor(name: impl Into<String>, sell_in: i32, quality: i32, floor: i32) -> Item {
+        Item {
+            quality_floor: Some(floor),
+            ..Item::new(name, sell_in, quality)
         }
     }
+
+    pub fn try_new(name: impl Into<String>, sell_in: i32, quality: i32) -> Result<Item, ItemError> {
+        let item = Item::new(name, sell_in, quality);
+        Rules::default().check_quality(&item, quality)?;
+        Ok(item)
+    }
+
+    /// Sets the quality if it is within this item's kind bounds under the default rules.
+    /// [`GildedRose::set_quality`] runs the same check under a shop's rules.
+    pub fn set_quality(&mut self, quality: i32) -> Result<(), ItemError> {
+        Rules::default().check_quality(self, quality)?;
+        self.quality = quality;
+        Ok(())
+    }
+
+    /// Sets the sell_in unless this item is legendary under the default rules, whose
+    /// sell_in never changes. [`GildedRose::set_sell_in`] runs the same check under a
+    /// shop's rules.
+    pub fn set_sell_in(&mut self, sell_in: i32) -> Result<(), ItemError> {
+        Rules::default().check_sell_in(self, sell_in)?;
+        self.sell_in = sell_in;
+        Ok(())
+    }
+
+    pub fn kind(&self) -> ItemKind {
+        self.kind.unwrap_or_else(|| ItemKind::from_name(&self.name))
+    }
+
+    /// Whether the item is legendary under the built-in rules. Names registered with
+    /// [`GildedRose::register_legendary`] are only known to their shop, see
+    /// [`GildedRose::partition_legendary`].
+    pub fn is_legendary(&self) -> bool {
+        self.kind() == ItemKind::Sulfuras
+    }
+
+    /// Whether the sell date has passed. Legendary items never expire. Like
+    /// [`Item::is_legendary`] this only knows the built-in rules, see
+    /// [`GildedRose::expired_items`] for a shop's own legendaries.
+    pub fn is_expired(&self) -> bool {
+        self.sell_in < 0 && !self.is_legendary()
+    }
+
+    /// Returns this item after `days` updates under the default rules, as
+    /// [`next_item_state`] applied `days` times.
+    pub fn age(&self, days: u32) -> Item {
+        let rules = Rules::default();
+        (0..days).fold(self.clone(), |item, _| rules.next_item(&item))
+    }
+
+    /// The name without a leading kind tag such as `[legendary]`.
+    pub fn display_name(&self) -> &str {
+        ItemKind::split_tag(&self.name).1
+    }
+
+    pub fn display_verbose(&self) -> ItemVerbose<'_> {
+        ItemVerbose(self)
+    }
+
+    pub fn freeze(&mut self) {
+        self.frozen = true;
+    }
+
+    pub fn unfreeze(&mut self) {
+        self.frozen = false;
+    }
+
+    fn skips_updates(&self) -> bool {
+        self.frozen || self.archived
+    }
+}
+
+/// Builds an [`Item`] with named fields.
+///
+/// `sell_in` and `quality` default to 0, except that legendary items default to quality 80.
+///
+/// ```
+/// use rust::gildedrose::{Item, ItemBuilder};
+///
+/// let brie = ItemBuilder::new().name("Aged Brie").sell_in(2).quality(10).build();
+/// assert_eq!(brie, Item::new("Aged Brie", 2, 10));
+///
+/// let sulfuras = ItemBuilder::new().name("Sulfuras, Hand of Ragnaros").build();
+/// assert_eq!(sulfuras.quality, 80);
+/// ```
+#[derive(Clone, Default, Debug)]
+pub struct ItemBuilder {
+    name: String,
+    sell_in: i32,
+    quality: Option<i32>,
+}
+
+impl ItemBuilder {
+    pub fn new() -> ItemBuilder {
+        ItemBuilder::default()
+    }
+
+    pub fn name(mut self, name: impl Into<String>) -> ItemBuilder {
+        self.name = name.into();
+        self
+    }
+
+    pub fn sell_in(mut self, sell_in: i32) -> ItemBuilder {
+        self.sell_in = sell_in;
+        self
+    }
+
+    pub fn quality(mut self, quality: i32) -> ItemBuilder {
+        self.quality = Some(quality);
+        self
+    }
+
+    pub fn build(self) -> Item {
+        let mut item = Item::new(self.name, self.sell_in, 0);
+        item.quality = match self.quality {
+            Some(quality) => quality,
+            None if item.is_legendary() => LEGENDARY_QUALITY,
+            None => 0,
+        };
+        item
+    }
 }
 
 impl Display for Item {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}, {}, {}", self.name, self.sell_in, self.quality)
+        write!(
+            f,
+            "{}, {}, {}",
+            self.display_name(),
+            self.sell_in,
+            self.quality
+        )
+    }
+}
+
+/// Displays an item with its kind, like `Aged Brie [AgedBrie] sell_in=10 quality=20`.
+pub struct ItemVerbose<'a>(pub &'a Item);
+
+impl Display for ItemVerbose<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let item = self.0;
+        write!(
+            f,
+            "{} [{:?}] sell_in={} quality={}",
+            item.display_name(),
+            item.kind(),
+            item.sell_in,
+            item.quality
+        )
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum ItemError {
+    QualityTooHigh {
+        name: String,
+        quality: i32,
+        max: i32,
+    },
+    /// A quality below the kind's lower bound, which is 0 under the default policy.
+    QualityNegative {
+        name: String,
+        quality: i32,
+        min: i32,
+    },
+    InvalidLegendaryQuality {
+        name: String,
+        quality: i32,
+    },
+    /// A legendary item's sell_in differs from an earlier snapshot, although it never
+    /// changes on update.
+    LegendarySellInChanged {
+        name: String,
+        sell_in: i32,
+        expected: i32,
+    },
+    /// A sell_in below the configured [`QualityConfig::min_sell_in`].
+    SellInTooLow {
+        name: String,
+        sell_in: i32,
+        min: i32,
+    },
+}
+
+impl Display for ItemError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ItemError::QualityTooHigh { name, quality, max } => {
+                write!(f, "quality {} of {} is above {}", quality, name, max)
+            }
+            ItemError::QualityNegative { name, quality, min } => {
+                write!(f, "quality {} of {} is below {}", quality, name, min)
+            }
+            ItemError::InvalidLegendaryQuality { name, quality } => {
+                write!(f, "legendary {} has invalid quality {}", name, quality)
+            }
+            ItemError::SellInTooLow { name, sell_in, min } => {
+                write!(f, "sell_in {} of {} is below {}", sell_in, name, min)
+            }
+            ItemError::LegendarySellInChanged {
+                name,
+                sell_in,
+                expected,
+            } => write!(
+                f,
+                "legendary {} has sell_in {} instead of {}",
+                name, sell_in, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ItemError {}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct DuplicateNameError {
+    pub name: String,
+}
+
+impl Display for DuplicateNameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "item name {} appears more than once", self.name)
+    }
+}
+
+impl std::error::Error for DuplicateNameError {}
+
+/// A name one typo away from a special item, which would silently update as a normal one.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct SuspiciousName {
+    pub name: String,
+    pub resembles: ItemKind,
+}
+
+impl Display for SuspiciousName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} looks like a misspelled {:?}",
+            self.name, self.resembles
+        )
+    }
+}
+
+impl std::error::Error for SuspiciousName {}
+
+/// The start of the names of special kinds, lowercase.
+const SPECIAL_NAMES: [(&str, ItemKind); 5] = [
+    ("aged brie", ItemKind::AgedBrie),
+    ("backstage passes", ItemKind::BackstagePasses),
+    ("sulfuras", ItemKind::Sulfuras),
+    ("conjured", ItemKind::Conjured),
+    ("cheese wheel", ItemKind::Ripening),
+];
+
+/// The special kind a normal item's name is one edit away from, if any.
+fn resembled_kind(item: &Item) -> Option<ItemKind> {
+    if item.kind() != ItemKind::Normal {
+        return None;
+    }
+    let name: Vec<char> = item.name.to_ascii_lowercase().chars().collect();
+    SPECIAL_NAMES.iter().find_map(|&(special, kind)| {
+        let special: Vec<char> = special.chars().collect();
+        let distances: Vec<usize> = (special.len() - 1..=special.len() + 1)
+            .map(|len| edit_distance(&name[..len.min(name.len())], &special))
+            .collect();
+        // an exact prefix, as in "Aged Brie Crumbs", is a different item, not a typo
+        if !distances.contains(&0) && distances.contains(&1) {
+            Some(kind)
+        } else {
+            None
+        }
+    })
+}
+
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.iter().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
     }
+    previous[b.len()]
 }
 
-trait CalculateQuality {
-    fn calculate_new_quality(&self, sell_in: i32, quality: i32) -> i32;
+pub trait CalculateQuality {
+    fn calculate_new_quality(&self, sell_in: i32, quality: i32, config: &QualityConfig) -> i32;
 }
 
-trait CalculateSellIn {
+pub trait CalculateSellIn {
     fn calculate_new_sell_in(&self, sell_in: i32) -> i32 {
-        sell_in - 1
+        sell_in.saturating_sub(1)
     }
 }
 
@@ -41,420 +503,6684 @@ impl AgedBrie {
 }
 
 impl CalculateQuality for AgedBrie {
-    fn calculate_new_quality(&self, sell_in: i32, quality: i32) -> i32 {
-        (quality + Self::calculate_quality_increment(sell_in)).min(50)
+    fn calculate_new_quality(&self, sell_in: i32, quality: i32, config: &QualityConfig) -> i32 {
+        // out-of-range input is normalized when the result is clamped to the policy's range
+        quality.saturating_add(config.cap_appreciation(Self::calculate_quality_increment(sell_in)))
     }
 }
 
 impl CalculateSellIn for AgedBrie {}
 
-struct BackstagePasses;
+struct BackstagePasses;
+
+impl BackstagePasses {
+    fn calculate_item_quality_increment(&self, sell_in: i32, config: &BackstageConfig) -> i32 {
+        match sell_in {
+            days if days <= config.tier3_days => 3,
+            days if days <= config.tier2_days => 2,
+            _ => 1,
+        }
+    }
+}
+
+impl CalculateQuality for BackstagePasses {
+    fn calculate_new_quality(&self, sell_in: i32, quality: i32, config: &QualityConfig) -> i32 {
+        if sell_in <= 0 {
+            // once the concert is over a pass is worthless, however often it is updated
+            return 0;
+        }
+        let increment = self.calculate_item_quality_increment(sell_in, &config.backstage);
+        quality.saturating_add(config.cap_appreciation(increment))
+    }
+}
+
+impl CalculateSellIn for BackstagePasses {}
+
+struct Legendary {
+    fixed_quality: i32,
+}
+
+impl CalculateQuality for Legendary {
+    fn calculate_new_quality(&self, _: i32, _: i32, _: &QualityConfig) -> i32 {
+        self.fixed_quality
+    }
+}
+
+impl CalculateSellIn for Legendary {
+    fn calculate_new_sell_in(&self, sell_in: i32) -> i32 {
+        sell_in
+    }
+}
+
+struct DefaultItem {
+    multiplier: i32,
+    /// Overrides the configured decay profile for this item.
+    profile: Option<DecayProfile>,
+}
+
+impl DefaultItem {
+    fn calculate_item_quality_increment(&self, sell_in: i32, profile: &DecayProfile) -> i32 {
+        profile.increment(sell_in).saturating_mul(self.multiplier)
+    }
+}
+
+impl CalculateQuality for DefaultItem {
+    fn calculate_new_quality(&self, sell_in: i32, quality: i32, config: &QualityConfig) -> i32 {
+        let profile = self.profile.as_ref().unwrap_or(&config.decay_profile);
+        quality.saturating_add(self.calculate_item_quality_increment(sell_in, profile))
+    }
+}
+
+struct Conjured {
+    multiplier: i32,
+}
+
+impl Conjured {
+    fn calculate_quality_increment(&self, sell_in: i32, config: &ConjuredConfig) -> i32 {
+        DefaultQualityIncrement::get(sell_in)
+            .saturating_mul(config.multiplier)
+            .saturating_mul(self.multiplier)
+    }
+}
+
+impl CalculateQuality for Conjured {
+    fn calculate_new_quality(&self, sell_in: i32, quality: i32, config: &QualityConfig) -> i32 {
+        quality.saturating_add(self.calculate_quality_increment(sell_in, &config.conjured))
+    }
+}
+
+impl CalculateSellIn for Conjured {}
+
+/// Gains 1 quality per day while sell_in is above `peak_day`, then spoils by 2 per day.
+struct Ripening {
+    peak_day: i32,
+}
+
+impl CalculateQuality for Ripening {
+    fn calculate_new_quality(&self, sell_in: i32, quality: i32, _: &QualityConfig) -> i32 {
+        if sell_in > self.peak_day {
+            quality.saturating_add(1)
+        } else {
+            quality.saturating_sub(2)
+        }
+    }
+}
+
+impl CalculateSellIn for Ripening {}
+
+impl CalculateSellIn for DefaultItem {}
+
+pub trait Calculations: CalculateQuality + CalculateSellIn {
+    /// The lowest and highest quality this kind may have under any policy. `None`, the
+    /// default, leaves the range to the shop's [`QualityPolicy`].
+    fn quality_bounds(&self, _config: &QualityConfig) -> Option<(i32, i32)> {
+        None
+    }
+
+    fn clamp_quality(&self, raw: i32, policy: &dyn QualityPolicy, config: &QualityConfig) -> i32 {
+        clamp_for_kind(policy, raw, self.quality_bounds(config))
+    }
+
+    /// The quality a legendary item always has, `None` for everything else.
+    fn fixed_quality(&self) -> Option<i32> {
+        None
+    }
+}
+
+pub trait QualityPolicy {
+    fn clamp(&self, raw: i32) -> i32;
+
+    /// Clamps for a kind with its own quality bounds, which replace the policy's range.
+    fn clamp_within(&self, raw: i32, (min_quality, max_quality): (i32, i32)) -> i32 {
+        raw.max(min_quality).min(max_quality)
+    }
+}
+
+/// Applies the kind's own bounds if it has any, the policy's range otherwise.
+fn clamp_for_kind(policy: &dyn QualityPolicy, raw: i32, bounds: Option<(i32, i32)>) -> i32 {
+    match bounds {
+        Some(bounds) => policy.clamp_within(raw, bounds),
+        None => policy.clamp(raw),
+    }
+}
+
+/// Keeps quality within `min_quality..=max_quality`, 0..=50 by default. Kinds with their
+/// own [`Calculations::quality_bounds`] use those instead.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct StandardPolicy {
+    pub min_quality: i32,
+    pub max_quality: i32,
+}
+
+impl Default for StandardPolicy {
+    fn default() -> StandardPolicy {
+        StandardPolicy::from(&QualityConfig::default())
+    }
+}
+
+impl From<&QualityConfig> for StandardPolicy {
+    fn from(config: &QualityConfig) -> StandardPolicy {
+        StandardPolicy {
+            min_quality: config.min_quality,
+            max_quality: config.max_quality,
+        }
+    }
+}
+
+impl QualityPolicy for StandardPolicy {
+    fn clamp(&self, raw: i32) -> i32 {
+        raw.max(self.min_quality).min(self.max_quality)
+    }
+}
+
+pub type NamePredicate = fn(&str) -> bool;
+
+pub type CalculatorConstructor = fn() -> Box<dyn Calculations>;
+
+#[derive(Clone)]
+struct CalculatorFactory {
+    custom: Vec<(NamePredicate, CalculatorConstructor)>,
+    legendaries: Vec<(String, i32)>,
+    decay_profiles: Vec<(NamePredicate, DecayProfile)>,
+    /// The quality built-in legendary items are held at.
+    legendary_quality: i32,
+}
+
+impl From<&QualityConfig> for CalculatorFactory {
+    fn from(config: &QualityConfig) -> CalculatorFactory {
+        CalculatorFactory {
+            custom: Vec::new(),
+            legendaries: Vec::new(),
+            decay_profiles: Vec::new(),
+            legendary_quality: config.legendary.expected_quality,
+        }
+    }
+}
+
+impl Default for CalculatorFactory {
+    fn default() -> CalculatorFactory {
+        CalculatorFactory::from(&QualityConfig::default())
+    }
+}
+
+impl Calculations for DefaultItem {}
+
+impl Calculations for Legendary {
+    fn quality_bounds(&self, _: &QualityConfig) -> Option<(i32, i32)> {
+        Some((self.fixed_quality, self.fixed_quality))
+    }
+
+    fn clamp_quality(&self, raw: i32, _: &dyn QualityPolicy, _: &QualityConfig) -> i32 {
+        raw
+    }
+
+    fn fixed_quality(&self) -> Option<i32> {
+        Some(self.fixed_quality)
+    }
+}
+
+impl Calculations for BackstagePasses {}
+
+impl Calculations for AgedBrie {}
+
+impl Calculations for Conjured {}
+
+impl Calculations for Ripening {}
+
+/// The built-in calculators as an enum, so updates can dispatch through a `match`
+/// instead of a boxed trait object.
+enum CalculatorKind {
+    AgedBrie(AgedBrie),
+    BackstagePasses(BackstagePasses),
+    Legendary(Legendary),
+    Conjured(Conjured),
+    Ripening(Ripening),
+    Default(DefaultItem),
+}
+
+impl CalculatorKind {
+    fn into_boxed(self) -> Box<dyn Calculations> {
+        match self {
+            CalculatorKind::AgedBrie(calculator) => Box::new(calculator),
+            CalculatorKind::BackstagePasses(calculator) => Box::new(calculator),
+            CalculatorKind::Legendary(calculator) => Box::new(calculator),
+            CalculatorKind::Conjured(calculator) => Box::new(calculator),
+            CalculatorKind::Ripening(calculator) => Box::new(calculator),
+            CalculatorKind::Default(calculator) => Box::new(calculator),
+        }
+    }
+
+    #[cfg(feature = "log")]
+    fn name(&self) -> &'static str {
+        match self {
+            CalculatorKind::AgedBrie(_) => "AgedBrie",
+            CalculatorKind::BackstagePasses(_) => "BackstagePasses",
+            CalculatorKind::Legendary(_) => "Legendary",
+            CalculatorKind::Conjured(_) => "Conjured",
+            CalculatorKind::Ripening(_) => "Ripening",
+            CalculatorKind::Default(_) => "Default",
+        }
+    }
+}
+
+impl CalculateQuality for CalculatorKind {
+    fn calculate_new_quality(&self, sell_in: i32, quality: i32, config: &QualityConfig) -> i32 {
+        match self {
+            CalculatorKind::AgedBrie(c) => c.calculate_new_quality(sell_in, quality, config),
+            CalculatorKind::BackstagePasses(c) => c.calculate_new_quality(sell_in, quality, config),
+            CalculatorKind::Legendary(c) => c.calculate_new_quality(sell_in, quality, config),
+            CalculatorKind::Conjured(c) => c.calculate_new_quality(sell_in, quality, config),
+            CalculatorKind::Ripening(c) => c.calculate_new_quality(sell_in, quality, config),
+            CalculatorKind::Default(c) => c.calculate_new_quality(sell_in, quality, config),
+        }
+    }
+}
+
+impl CalculateSellIn for CalculatorKind {
+    fn calculate_new_sell_in(&self, sell_in: i32) -> i32 {
+        match self {
+            CalculatorKind::AgedBrie(c) => c.calculate_new_sell_in(sell_in),
+            CalculatorKind::BackstagePasses(c) => c.calculate_new_sell_in(sell_in),
+            CalculatorKind::Legendary(c) => c.calculate_new_sell_in(sell_in),
+            CalculatorKind::Conjured(c) => c.calculate_new_sell_in(sell_in),
+            CalculatorKind::Ripening(c) => c.calculate_new_sell_in(sell_in),
+            CalculatorKind::Default(c) => c.calculate_new_sell_in(sell_in),
+        }
+    }
+}
+
+impl Calculations for CalculatorKind {
+    fn quality_bounds(&self, config: &QualityConfig) -> Option<(i32, i32)> {
+        match self {
+            CalculatorKind::AgedBrie(c) => c.quality_bounds(config),
+            CalculatorKind::BackstagePasses(c) => c.quality_bounds(config),
+            CalculatorKind::Legendary(c) => c.quality_bounds(config),
+            CalculatorKind::Conjured(c) => c.quality_bounds(config),
+            CalculatorKind::Ripening(c) => c.quality_bounds(config),
+            CalculatorKind::Default(c) => c.quality_bounds(config),
+        }
+    }
+
+    fn clamp_quality(&self, raw: i32, policy: &dyn QualityPolicy, config: &QualityConfig) -> i32 {
+        match self {
+            CalculatorKind::AgedBrie(c) => c.clamp_quality(raw, policy, config),
+            CalculatorKind::BackstagePasses(c) => c.clamp_quality(raw, policy, config),
+            CalculatorKind::Legendary(c) => c.clamp_quality(raw, policy, config),
+            CalculatorKind::Conjured(c) => c.clamp_quality(raw, policy, config),
+            CalculatorKind::Ripening(c) => c.clamp_quality(raw, policy, config),
+            CalculatorKind::Default(c) => c.clamp_quality(raw, policy, config),
+        }
+    }
+
+    fn fixed_quality(&self) -> Option<i32> {
+        match self {
+            CalculatorKind::AgedBrie(c) => c.fixed_quality(),
+            CalculatorKind::BackstagePasses(c) => c.fixed_quality(),
+            CalculatorKind::Legendary(c) => c.fixed_quality(),
+            CalculatorKind::Conjured(c) => c.fixed_quality(),
+            CalculatorKind::Ripening(c) => c.fixed_quality(),
+            CalculatorKind::Default(c) => c.fixed_quality(),
+        }
+    }
+}
+
+impl CalculatorFactory {
+    fn register(&mut self, predicate: NamePredicate, constructor: CalculatorConstructor) {
+        self.custom.push((predicate, constructor));
+    }
+
+    fn register_decay_profile(&mut self, predicate: NamePredicate, profile: DecayProfile) {
+        self.decay_profiles.push((predicate, profile));
+    }
+
+    fn register_legendary(&mut self, name: String, fixed_quality: i32) {
+        self.legendaries.push((name, fixed_quality));
+    }
+
+    fn create_calculator(&self, item: &Item) -> Box<dyn Calculations> {
+        self.custom_calculator(item)
+            .unwrap_or_else(|| self.calculator_kind(item).into_boxed())
+    }
+
+    fn custom_calculator(&self, item: &Item) -> Option<Box<dyn Calculations>> {
+        self.custom
+            .iter()
+            .find(|(predicate, _)| predicate(&item.name))
+            .map(|(_, constructor)| constructor())
+    }
+
+    #[cfg(feature = "log")]
+    fn calculator_name(&self, item: &Item) -> &'static str {
+        if self
+            .custom
+            .iter()
+            .any(|(predicate, _)| predicate(&item.name))
+        {
+            "Custom"
+        } else {
+            self.calculator_kind(item).name()
+        }
+    }
+
+    /// Picks the built-in calculator for `item`, ignoring custom registrations.
+    fn calculator_kind(&self, item: &Item) -> CalculatorKind {
+        if let Some((_, fixed_quality)) =
+            self.legendaries.iter().find(|(name, _)| *name == item.name)
+        {
+            return CalculatorKind::Legendary(Legendary {
+                fixed_quality: *fixed_quality,
+            });
+        }
+        match item.kind() {
+            ItemKind::AgedBrie => CalculatorKind::AgedBrie(AgedBrie),
+            ItemKind::BackstagePasses => CalculatorKind::BackstagePasses(BackstagePasses),
+            ItemKind::Sulfuras => CalculatorKind::Legendary(Legendary {
+                fixed_quality: self.legendary_quality,
+            }),
+            ItemKind::Conjured => CalculatorKind::Conjured(Conjured {
+                multiplier: item.decay_multiplier,
+            }),
+            ItemKind::Ripening => CalculatorKind::Ripening(Ripening {
+                peak_day: RIPENING_PEAK_DAY,
+            }),
+            ItemKind::Normal => CalculatorKind::Default(DefaultItem {
+                multiplier: item.decay_multiplier,
+                profile: self
+                    .decay_profiles
+                    .iter()
+                    .find(|(predicate, _)| predicate(&item.name))
+                    .map(|(_, profile)| *profile),
+            }),
+        }
+    }
+}
+
+/// Snapshot of the mutable part of an item, so a new state is always derived from
+/// the complete previous state and written back in one go.
+#[derive(Clone, Copy)]
+struct ItemState {
+    sell_in: i32,
+    quality: i32,
+    /// Whether clamping changed the computed quality on the way to this state.
+    clamped: bool,
+    /// The item's own [`Item::quality_floor`], carried along but never written back.
+    quality_floor: Option<i32>,
+}
+
+impl ItemState {
+    fn of(item: &Item) -> ItemState {
+        ItemState {
+            sell_in: item.sell_in,
+            quality: item.quality,
+            clamped: false,
+            quality_floor: item.quality_floor,
+        }
+    }
+
+    fn next<C: Calculations + ?Sized>(self, calculator: &C, rules: &Rules) -> ItemState {
+        let raw_quality =
+            calculator.calculate_new_quality(self.sell_in, self.quality, &rules.config);
+        let quality = calculator
+            .clamp_quality(raw_quality, rules.policy.as_ref(), &rules.config)
+            .max(rules.quality_floor(calculator));
+        let clamped = self.clamped || quality != raw_quality;
+        let quality = match self.quality_floor {
+            Some(floor) if calculator.fixed_quality().is_none() => {
+                quality.max(floor.min(self.quality))
+            }
+            _ => quality,
+        };
+        let sell_in = calculator.calculate_new_sell_in(self.sell_in);
+        ItemState {
+            sell_in: rules.config.floor_sell_in(self.sell_in, sell_in),
+            quality,
+            clamped,
+            ..self
+        }
+    }
+
+    /// Applies one update, which is as many days as the configured time step.
+    fn advance<C: Calculations + ?Sized>(self, calculator: &C, rules: &Rules) -> ItemState {
+        (0..rules.config.time_step.days).fold(self, |state, _| state.next(calculator, rules))
+    }
+
+    fn write_to(self, item: &mut Item) {
+        item.sell_in = self.sell_in;
+        item.quality = self.quality;
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ItemChange {
+    pub name: String,
+    pub quality_delta: i32,
+    pub sell_in_delta: i32,
+    /// Whether the computed quality fell outside the bounds and was clamped.
+    pub clamped: bool,
+}
+
+/// A difference between two shops, as reported by [`GildedRose::diff`].
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum ItemDiff {
+    /// An item in both shops whose quality or sell_in differs, as `(self, other)` pairs.
+    Changed {
+        name: String,
+        quality: (i32, i32),
+        sell_in: (i32, i32),
+    },
+    /// An item only in the other shop.
+    Added(Item),
+    /// An item only in this shop.
+    Removed(Item),
+}
+
+/// Common aggregates over a shop, as returned by [`GildedRose::summary`].
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct InventorySummary {
+    pub count: usize,
+    /// Same as [`GildedRose::count_expired`].
+    pub expired: usize,
+    /// Same as [`GildedRose::total_quality`].
+    pub total_quality: i64,
+    /// Same as [`GildedRose::average_quality`].
+    pub average_quality: Option<f64>,
+    pub legendary_count: usize,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct QualityConfig {
+    pub max_quality: i32,
+    pub min_quality: i32,
+    pub backstage: BackstageConfig,
+    pub conjured: ConjuredConfig,
+    pub decay_profile: DecayProfile,
+    pub time_step: TimeStep,
+    /// Limits the daily quality gain of Aged Brie and Backstage passes.
+    pub appreciation_cap: Option<i32>,
+    pub legendary: LegendaryConfig,
+    /// Lowest value sell_in counts down to. Items already below it stay where they are.
+    pub min_sell_in: Option<i32>,
+}
+
+impl QualityConfig {
+    fn cap_appreciation(&self, increment: i32) -> i32 {
+        match self.appreciation_cap {
+            Some(cap) => increment.min(cap),
+            None => increment,
+        }
+    }
+
+    fn floor_sell_in(&self, current: i32, next: i32) -> i32 {
+        match self.min_sell_in {
+            Some(floor) if next < current => next.max(floor.min(current)),
+            _ => next,
+        }
+    }
+}
+
+impl Default for QualityConfig {
+    fn default() -> QualityConfig {
+        QualityConfig {
+            max_quality: MAX_QUALITY,
+            min_quality: MIN_QUALITY,
+            backstage: BackstageConfig::default(),
+            conjured: ConjuredConfig::default(),
+            decay_profile: DecayProfile::default(),
+            time_step: TimeStep::default(),
+            appreciation_cap: None,
+            legendary: LegendaryConfig::default(),
+            min_sell_in: None,
+        }
+    }
+}
+
+/// How many times faster than a normal item Conjured items decay.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ConjuredConfig {
+    pub multiplier: i32,
+}
+
+impl Default for ConjuredConfig {
+    fn default() -> ConjuredConfig {
+        ConjuredConfig {
+            multiplier: CONJURED_DECAY_MULTIPLIER,
+        }
+    }
+}
+
+/// The quality built-in legendary items hold, and how far off it
+/// [`GildedRose::update_quality_checked`] accepts before reporting an error. Registered
+/// legendaries are checked against their own fixed quality with the same tolerance.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct LegendaryConfig {
+    pub expected_quality: i32,
+    pub tolerance: i32,
+}
+
+impl LegendaryConfig {
+    fn accepts(&self, quality: i32, fixed_quality: i32) -> bool {
+        (i64::from(quality) - i64::from(fixed_quality)).abs() <= i64::from(self.tolerance)
+    }
+}
+
+impl Default for LegendaryConfig {
+    fn default() -> LegendaryConfig {
+        LegendaryConfig {
+            expected_quality: LEGENDARY_QUALITY,
+            tolerance: 0,
+        }
+    }
+}
+
+/// How many days pass with each update. Every day is applied in turn, so items cross
+/// their sell date and tier boundaries just as with single-day updates.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct TimeStep {
+    pub days: u32,
+}
+
+impl Default for TimeStep {
+    fn default() -> TimeStep {
+        TimeStep { days: 1 }
+    }
+}
+
+/// Days before the concert at which Backstage passes start gaining 2 and 3 quality per day.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct BackstageConfig {
+    pub tier2_days: i32,
+    pub tier3_days: i32,
+}
+
+impl Default for BackstageConfig {
+    fn default() -> BackstageConfig {
+        BackstageConfig {
+            tier2_days: BACKSTAGE_TIER2_DAYS,
+            tier3_days: BACKSTAGE_TIER3_DAYS,
+        }
+    }
+}
+
+/// How fast normal items lose quality, depending on their sell_in.
+#[derive(Clone, Copy, Default, Debug)]
+pub enum DecayProfile {
+    /// Loses 1 per day, even after the sell date.
+    Linear,
+    /// Loses 1 per day, and 2 once the sell date has passed.
+    #[default]
+    DoubleAfterExpiry,
+    /// Loses 1 per day, and 3 once the sell date has passed.
+    TripleAfterExpiry,
+    /// Computes the daily increment from the sell_in.
+    Custom(fn(i32) -> i32),
+}
+
+impl DecayProfile {
+    pub fn increment(&self, sell_in: i32) -> i32 {
+        match self {
+            DecayProfile::Linear => QUALITY_INCREMENT,
+            DecayProfile::DoubleAfterExpiry => DefaultQualityIncrement::get(sell_in),
+            DecayProfile::TripleAfterExpiry => {
+                if sell_in < 1 {
+                    QUALITY_INCREMENT * 3
+                } else {
+                    QUALITY_INCREMENT
+                }
+            }
+            DecayProfile::Custom(increment) => increment(sell_in),
+        }
+    }
+}
+
+/// Everything besides the items that decides how an update behaves.
+#[derive(Clone)]
+struct Rules {
+    config: QualityConfig,
+    factory: CalculatorFactory,
+    policy: Arc<dyn QualityPolicy + Send + Sync>,
+}
+
+impl Rules {
+    fn new(config: QualityConfig) -> Rules {
+        Rules {
+            config,
+            factory: CalculatorFactory::from(&config),
+            policy: Arc::new(StandardPolicy::from(&config)),
+        }
+    }
+
+    fn next_state(&self, item: &Item) -> ItemState {
+        if item.skips_updates() {
+            return ItemState::of(item);
+        }
+        let state = ItemState::of(item);
+        // only custom calculators need boxing; built-ins dispatch through the enum
+        match self.factory.custom_calculator(item) {
+            Some(calculator) => state.advance(calculator.as_ref(), self),
+            None => state.advance(&self.factory.calculator_kind(item), self),
+        }
+    }
+
+    #[cfg(feature = "log")]
+    fn log_update(&self, item: &Item, next: &ItemState) {
+        log::debug!(
+            "updated {:?} with {} calculator: quality {} -> {}, sell_in {} -> {}",
+            item.name,
+            self.factory.calculator_name(item),
+            item.quality,
+            next.quality,
+            item.sell_in,
+            next.sell_in
+        );
+    }
+
+    fn next_item(&self, item: &Item) -> Item {
+        let mut next = item.clone();
+        self.next_state(item).write_to(&mut next);
+        next
+    }
+
+    /// The lowest quality the policy allows for the calculator's kind, 0 by default.
+    /// Applied after every calculation so that calculators overriding
+    /// [`Calculations::clamp_quality`] cannot store a quality below it.
+    fn quality_floor<C: Calculations + ?Sized>(&self, calculator: &C) -> i32 {
+        clamp_for_kind(
+            self.policy.as_ref(),
+            i32::MIN,
+            calculator.quality_bounds(&self.config),
+        )
+    }
+
+    /// The quality of `item` brought into its kind's bounds.
+    fn normalized_quality(&self, item: &Item) -> i32 {
+        let calculator = self.factory.create_calculator(item);
+        calculator.fixed_quality().unwrap_or_else(|| {
+            calculator.clamp_quality(item.quality, self.policy.as_ref(), &self.config)
+        })
+    }
+
+    /// Checks `quality` against the bounds of `item`'s kind under these rules. Legendary
+    /// items may be off their fixed quality by the [`LegendaryConfig`] tolerance.
+    fn check_quality(&self, item: &Item, quality: i32) -> Result<(), ItemError> {
+        let calculator = self.factory.create_calculator(item);
+        let name = item.name.clone();
+        if let Some(fixed_quality) = calculator.fixed_quality() {
+            return if self.config.legendary.accepts(quality, fixed_quality) {
+                Ok(())
+            } else {
+                Err(ItemError::InvalidLegendaryQuality { name, quality })
+            };
+        }
+        let bounds = calculator.quality_bounds(&self.config);
+        let policy = self.policy.as_ref();
+        let (min, max) = (
+            clamp_for_kind(policy, i32::MIN, bounds),
+            clamp_for_kind(policy, i32::MAX, bounds),
+        );
+        if quality > max {
+            Err(ItemError::QualityTooHigh { name, quality, max })
+        } else if quality < min {
+            Err(ItemError::QualityNegative { name, quality, min })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Checks that `sell_in` keeps a legendary `item`'s sell_in unchanged and is not below
+    /// the configured [`QualityConfig::min_sell_in`].
+    fn check_sell_in(&self, item: &Item, sell_in: i32) -> Result<(), ItemError> {
+        let name = item.name.clone();
+        if self.is_legendary(item) && sell_in != item.sell_in {
+            return Err(ItemError::LegendarySellInChanged {
+                name,
+                sell_in,
+                expected: item.sell_in,
+            });
+        }
+        match self.config.min_sell_in {
+            Some(min) if sell_in < min => Err(ItemError::SellInTooLow { name, sell_in, min }),
+            _ => Ok(()),
+        }
+    }
+
+    fn is_legendary(&self, item: &Item) -> bool {
+        self.factory
+            .create_calculator(item)
+            .fixed_quality()
+            .is_some()
+    }
+}
+
+impl Default for Rules {
+    fn default() -> Rules {
+        Rules::new(QualityConfig::default())
+    }
+}
+
+/// Returns `item` as it looks after one day under the default rules.
+///
+/// This is the same per-item step [`GildedRose::update_quality`] applies, minus any
+/// configuration or registrations the shop carries.
+pub fn next_item_state(item: &Item) -> Item {
+    Rules::default().next_item(item)
+}
+
+/// Renders the quality of `item` today and after each of the next `days` updates under
+/// the default rules, one block character per day scaled to 0..=50.
+pub fn quality_sparkline(item: &Item, days: u32) -> String {
+    const BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let rules = Rules::default();
+    let mut current = item.clone();
+    let mut sparkline = String::new();
+    for day in 0..=days {
+        if day > 0 {
+            current = rules.next_item(&current);
+        }
+        let level = current.quality.clamp(MIN_QUALITY, MAX_QUALITY) * 7 / MAX_QUALITY;
+        sparkline.push(BLOCKS[level as usize]);
+    }
+    sparkline
+}
+
+/// Counts the updates under the default rules until `item` reaches quality 0, or
+/// `None` if it never will, like legendary items and Aged Brie.
+pub fn days_until_worthless(item: &Item) -> Option<u32> {
+    if item.quality <= 0 {
+        return Some(0);
+    }
+    if item.skips_updates() || item.quality_floor.is_some_and(|floor| floor > 0) {
+        return None;
+    }
+    match item.kind() {
+        ItemKind::Sulfuras | ItemKind::AgedBrie => None,
+        ItemKind::Normal | ItemKind::Conjured if item.decay_multiplier <= 0 => None,
+        // gains until the concert and is worthless the update after it
+        ItemKind::BackstagePasses => Some(item.sell_in.max(0) as u32 + 1),
+        ItemKind::Ripening => {
+            // gains 1 a day up to the cap until its peak, so jump straight there
+            let days_to_peak = (i64::from(item.sell_in) - i64::from(RIPENING_PEAK_DAY)).max(0);
+            if days_to_peak == 0 {
+                return count_until_worthless(item);
+            }
+            let ripe = Item {
+                sell_in: RIPENING_PEAK_DAY,
+                quality: (i64::from(item.quality) + days_to_peak).min(i64::from(MAX_QUALITY))
+                    as i32,
+                ..item.clone()
+            };
+            count_until_worthless(&ripe).map(|days| days + days_to_peak as u32)
+        }
+        _ => count_until_worthless(item),
+    }
+}
+
+/// Steps `item` until it is worthless, for kinds that lose quality every update.
+fn count_until_worthless(item: &Item) -> Option<u32> {
+    let rules = Rules::default();
+    let mut current = item.clone();
+    let mut days = 0;
+    while current.quality > 0 {
+        let next = rules.next_item(&current);
+        // past the sell date decay only speeds up, so an item that stops losing
+        // quality then has stopped for good
+        if next.quality >= current.quality && (next == current || current.sell_in < 0) {
+            return None;
+        }
+        current = next;
+        days += 1;
+    }
+    Some(days)
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GildedRose {
+    pub items: Vec<Item>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    rules: Rules,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    history: Option<Vec<Vec<Item>>>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    day: u32,
+}
+
+impl GildedRose {
+    pub fn new(items: Vec<Item>) -> GildedRose {
+        GildedRose::with_config(items, QualityConfig::default())
+    }
+
+    pub fn with_config(items: Vec<Item>, config: QualityConfig) -> GildedRose {
+        GildedRose {
+            items,
+            rules: Rules::new(config),
+            history: None,
+            day: 0,
+        }
+    }
+
+    /// Like [`GildedRose::new`], but rejects inventories in which two items share a name,
+    /// reporting the first repeated name.
+    pub fn new_unique(items: Vec<Item>) -> Result<GildedRose, DuplicateNameError> {
+        let mut names = HashSet::new();
+        if let Some(duplicate) = items.iter().find(|item| !names.insert(item.name.as_str())) {
+            return Err(DuplicateNameError {
+                name: duplicate.name.clone(),
+            });
+        }
+        Ok(GildedRose::new(items))
+    }
+
+    /// Like [`GildedRose::new`], but rejects names one typo away from a special item, such
+    /// as "Sulfura", reporting the first one.
+    pub fn new_strict(items: Vec<Item>) -> Result<GildedRose, SuspiciousName> {
+        for item in &items {
+            if let Some(resembles) = resembled_kind(item) {
+                return Err(SuspiciousName {
+                    name: item.name.clone(),
+                    resembles,
+                });
+            }
+        }
+        Ok(GildedRose::new(items))
+    }
+
+    pub fn with_policy(
+        items: Vec<Item>,
+        policy: impl QualityPolicy + Send + Sync + 'static,
+    ) -> GildedRose {
+        let mut rose = GildedRose::new(items);
+        rose.rules.policy = Arc::new(policy);
+        rose
+    }
+
+    /// Iterates over the items in the shop.
+    ///
+    /// ```
+    /// use rust::gildedrose::{GildedRose, Item};
+    ///
+    /// let rose = GildedRose::new(vec![Item::new("Elixir", -1, 4), Item::new("Vest", 3, 20)]);
+    /// let expired: Vec<&Item> = rose.iter().filter(|item| item.sell_in < 0).collect();
+    ///
+    /// assert_eq!(expired, vec![&Item::new("Elixir", -1, 4)]);
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = &Item> {
+        self.items.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Item> {
+        self.items.iter_mut()
+    }
+
+    pub fn add_item(&mut self, item: Item) {
+        self.items.push(item);
+    }
+
+    /// Removes the first item named exactly `name`.
+    pub fn remove_item(&mut self, name: &str) -> Option<Item> {
+        let index = self.items.iter().position(|item| item.name == name)?;
+        Some(self.items.remove(index))
+    }
+
+    /// Appends the items of `other`, keeping this shop's rules.
+    pub fn merge(&mut self, other: GildedRose) {
+        self.items.extend(other.items);
+    }
+
+    /// Like [`GildedRose::merge`], but adds the quantity of an incoming item to an
+    /// existing item with the same name, sell_in and quality instead of appending it.
+    pub fn merge_stacking(&mut self, other: GildedRose) {
+        for incoming in other.items {
+            let stack = self.items.iter_mut().find(|item| {
+                item.name == incoming.name
+                    && item.sell_in == incoming.sell_in
+                    && item.quality == incoming.quality
+            });
+            match stack {
+                Some(stack) => stack.quantity = stack.quantity.saturating_add(incoming.quantity),
+                None => self.items.push(incoming),
+            }
+        }
+    }
+
+    /// Compares this shop with `other`, pairing items by name in order. Paired items that
+    /// agree on quality and sell_in are not reported.
+    pub fn diff(&self, other: &GildedRose) -> Vec<ItemDiff> {
+        let mut unmatched: Vec<Option<&Item>> = other.items.iter().map(Some).collect();
+        let mut diffs = Vec::new();
+        for item in &self.items {
+            let counterpart = unmatched
+                .iter_mut()
+                .find(|slot| slot.is_some_and(|candidate| candidate.name == item.name))
+                .and_then(Option::take);
+            match counterpart {
+                Some(counterpart) => {
+                    if counterpart.quality != item.quality || counterpart.sell_in != item.sell_in {
+                        diffs.push(ItemDiff::Changed {
+                            name: item.name.clone(),
+                            quality: (item.quality, counterpart.quality),
+                            sell_in: (item.sell_in, counterpart.sell_in),
+                        });
+                    }
+                }
+                None => diffs.push(ItemDiff::Removed(item.clone())),
+            }
+        }
+        diffs.extend(
+            unmatched
+                .into_iter()
+                .flatten()
+                .cloned()
+                .map(ItemDiff::Added),
+        );
+        diffs
+    }
+
+    /// Archives the first item named exactly `name`. Returns `false` if there is none.
+    pub fn archive(&mut self, name: &str) -> bool {
+        self.set_archived(name, true)
+    }
+
+    /// Unarchives the first item named exactly `name`. Returns `false` if there is none.
+    pub fn unarchive(&mut self, name: &str) -> bool {
+        self.set_archived(name, false)
+    }
+
+    fn set_archived(&mut self, name: &str, archived: bool) -> bool {
+        match self.find_by_name_mut(name) {
+            Some(item) => {
+                item.archived = archived;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn active_items(&self) -> Vec<&Item> {
+        self.items.iter().filter(|item| !item.archived).collect()
+    }
+
+    pub fn archived_items(&self) -> Vec<&Item> {
+        self.items.iter().filter(|item| item.archived).collect()
+    }
+
+    /// Renames every item for which `f` returns a new name and returns how many were
+    /// renamed. Items with no kind override are reclassified by their new name, so
+    /// renaming can change how they update.
+    pub fn rename_matching<F: Fn(&str) -> Option<String>>(&mut self, f: F) -> usize {
+        let mut renamed = 0;
+        for item in &mut self.items {
+            if let Some(name) = f(&item.name) {
+                item.name = name;
+                renamed += 1;
+            }
+        }
+        renamed
+    }
+
+    /// Returns the first item named exactly `name`.
+    pub fn find_by_name(&self, name: &str) -> Option<&Item> {
+        self.items.iter().find(|item| item.name == name)
+    }
+
+    pub fn find_by_name_mut(&mut self, name: &str) -> Option<&mut Item> {
+        self.items.iter_mut().find(|item| item.name == name)
+    }
+
+    pub fn retain<F: FnMut(&Item) -> bool>(&mut self, f: F) {
+        self.items.retain(f);
+    }
+
+    /// Returns a new shop with the same rules and day, holding clones of the items matching
+    /// `pred`. Unlike [`GildedRose::retain`], this shop is left unchanged.
+    pub fn filter_clone<F: Fn(&Item) -> bool>(&self, pred: F) -> GildedRose {
+        self.detached(
+            self.items
+                .iter()
+                .filter(|item| pred(item))
+                .cloned()
+                .collect(),
+        )
+    }
+
+    /// Removes every non-legendary item whose quality has dropped to 0.
+    pub fn discard_worthless(&mut self) {
+        let rules = &self.rules;
+        self.items
+            .retain(|item| item.quality != 0 || rules.is_legendary(item));
+    }
+
+    /// Sorts items from highest to lowest quality, keeping the order of equal qualities.
+    pub fn sort_by_quality(&mut self) {
+        self.items
+            .sort_by_key(|item| std::cmp::Reverse(item.quality));
+    }
+
+    /// Sorts items from soonest to latest sell date, keeping the order of equal sell_ins.
+    pub fn sort_by_sell_in(&mut self) {
+        self.items.sort_by_key(|item| item.sell_in);
+    }
+
+    /// Clamps every item's quality into its kind's bounds and resets legendary items to
+    /// their fixed quality, without advancing the day.
+    pub fn normalize(&mut self) {
+        let rules = &self.rules;
+        for item in &mut self.items {
+            item.quality = rules.normalized_quality(item);
+        }
+    }
+
+    /// Adds `delta` to the quality of every non-legendary item, clamped into its kind's
+    /// bounds like an update would.
+    pub fn apply_quality_delta(&mut self, delta: i32) {
+        let rules = &self.rules;
+        for item in self
+            .items
+            .iter_mut()
+            .filter(|item| !rules.is_legendary(item))
+        {
+            item.quality = rules.factory.create_calculator(item).clamp_quality(
+                item.quality.saturating_add(delta),
+                rules.policy.as_ref(),
+                &rules.config,
+            );
+        }
+    }
+
+    /// Splits the shop into its legendary and non-legendary items, in that order.
+    pub fn partition_legendary(self) -> (Vec<Item>, Vec<Item>) {
+        let rules = &self.rules;
+        self.items
+            .into_iter()
+            .partition(|item| rules.is_legendary(item))
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn group_by_kind(&self) -> HashMap<ItemKind, Vec<&Item>> {
+        let mut groups: HashMap<ItemKind, Vec<&Item>> = HashMap::new();
+        for item in &self.items {
+            groups.entry(item.kind()).or_default().push(item);
+        }
+        groups
+    }
+
+    pub fn count_expired(&self) -> usize {
+        self.expired().count()
+    }
+
+    pub fn expired_items(&self) -> Vec<&Item> {
+        self.expired().collect()
+    }
+
+    /// Returns the non-legendary item losing the most quality with the next update,
+    /// preferring the lowest sell_in on ties. `None` if no item declines.
+    pub fn fastest_declining(&self) -> Option<&Item> {
+        self.items
+            .iter()
+            .filter(|item| !self.rules.is_legendary(item))
+            .map(|item| {
+                let delta = self
+                    .rules
+                    .next_state(item)
+                    .quality
+                    .saturating_sub(item.quality);
+                (delta, item)
+            })
+            .filter(|(delta, _)| *delta < 0)
+            .min_by_key(|(delta, item)| (*delta, item.sell_in))
+            .map(|(_, item)| item)
+    }
+
+    /// Returns the non-legendary items whose quality will rise with the next update.
+    pub fn appreciating_items(&self) -> Vec<&Item> {
+        self.items
+            .iter()
+            .filter(|item| {
+                !self.rules.is_legendary(item) && self.rules.next_state(item).quality > item.quality
+            })
+            .collect()
+    }
+
+    /// Returns the non-legendary items that will be past their sell date after the
+    /// next update. Frozen and archived items are left out since they do not update.
+    pub fn expiring_next_update(&self) -> Vec<&Item> {
+        self.items
+            .iter()
+            .filter(|item| {
+                item.sell_in >= 0
+                    && !item.skips_updates()
+                    && !self.rules.is_legendary(item)
+                    && self.rules.next_state(item).sell_in < 0
+            })
+            .collect()
+    }
+
+    /// Sums the quality of every unit, legendary ones included, as `i64` so large shops
+    /// cannot overflow.
+    pub fn total_quality(&self) -> i64 {
+        self.items
+            .iter()
+            .map(|item| i64::from(item.quality) * i64::from(item.quantity))
+            .sum()
+    }
+
+    pub fn sum_sell_in(&self) -> i64 {
+        self.items.iter().map(|item| i64::from(item.sell_in)).sum()
+    }
+
+    /// Averages the quality per unit, so larger stacks weigh more.
+    pub fn average_quality(&self) -> Option<f64> {
+        let units: u64 = self.items.iter().map(|item| u64::from(item.quantity)).sum();
+        if units == 0 {
+            None
+        } else {
+            Some(self.total_quality() as f64 / units as f64)
+        }
+    }
+
+    /// Computes the common aggregates in a single pass over the items.
+    pub fn summary(&self) -> InventorySummary {
+        let mut expired = 0;
+        let mut legendary_count = 0;
+        let mut total_quality = 0;
+        let mut units: u64 = 0;
+        for item in &self.items {
+            if self.rules.is_legendary(item) {
+                legendary_count += 1;
+            } else if item.sell_in < 0 {
+                expired += 1;
+            }
+            total_quality += i64::from(item.quality) * i64::from(item.quantity);
+            units += u64::from(item.quantity);
+        }
+        InventorySummary {
+            count: self.items.len(),
+            expired,
+            total_quality,
+            average_quality: if units == 0 {
+                None
+            } else {
+                Some(total_quality as f64 / units as f64)
+            },
+            legendary_count,
+        }
+    }
+
+    /// Sums `price * quality * quantity` over all priced items. Negative quality counts
+    /// as 0.
+    pub fn total_value(&self) -> u64 {
+        self.items
+            .iter()
+            .filter_map(|item| {
+                item.price.map(|price| {
+                    (u64::from(price) * item.quality.max(0) as u64)
+                        .saturating_mul(u64::from(item.quantity))
+                })
+            })
+            .fold(0, u64::saturating_add)
+    }
+
+    /// Averages quality over priced items, weighted by price times quantity. `None` if no
+    /// item has a price.
+    pub fn value_weighted_quality(&self) -> Option<f64> {
+        let (weighted, weights) = self
+            .items
+            .iter()
+            .filter_map(|item| {
+                item.price
+                    .map(|price| (item, f64::from(price) * f64::from(item.quantity)))
+            })
+            .fold((0.0, 0.0), |(weighted, weights), (item, weight)| {
+                (
+                    weighted + f64::from(item.quality) * weight,
+                    weights + weight,
+                )
+            });
+        if weights == 0.0 {
+            None
+        } else {
+            Some(weighted / weights)
+        }
+    }
+
+    /// Counts items per quality bucket, keyed by each bucket's lower bound, so with a
+    /// `bucket_size` of 10 the key 10 counts qualities 10 through 19. A lower bound
+    /// below `i32::MIN` saturates to `i32::MIN`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bucket_size` is not positive.
+    pub fn quality_histogram(&self, bucket_size: i32) -> BTreeMap<i32, usize> {
+        assert!(bucket_size > 0, "bucket_size must be positive");
+        let mut histogram = BTreeMap::new();
+        for item in &self.items {
+            let bucket = item
+                .quality
+                .div_euclid(bucket_size)
+                .saturating_mul(bucket_size);
+            *histogram.entry(bucket).or_insert(0) += 1;
+        }
+        histogram
+    }
+
+    /// Returns the item with the highest quality, preferring the first one on ties.
+    pub fn highest_quality_item(&self) -> Option<&Item> {
+        // `max_by_key` keeps the last maximum, so walk backwards to keep the first.
+        self.items.iter().rev().max_by_key(|item| item.quality)
+    }
+
+    fn expired(&self) -> impl Iterator<Item = &Item> {
+        self.items
+            .iter()
+            .filter(move |item| item.sell_in < 0 && !self.rules.is_legendary(item))
+    }
+
+    /// Registers a calculator for items whose name matches `predicate`.
+    ///
+    /// Custom calculators are consulted in registration order before any built-in kind,
+    /// so the first matching predicate wins and unmatched items fall back to the
+    /// built-in dispatch.
+    pub fn register_calculator(
+        &mut self,
+        predicate: NamePredicate,
+        constructor: CalculatorConstructor,
+    ) {
+        self.rules.factory.register(predicate, constructor);
+    }
+
+    /// Makes normal items whose name matches `predicate` decay by `profile` instead of
+    /// the configured [`QualityConfig::decay_profile`]. The first matching registration
+    /// wins.
+    pub fn register_decay_profile(&mut self, predicate: NamePredicate, profile: DecayProfile) {
+        self.rules
+            .factory
+            .register_decay_profile(predicate, profile);
+    }
+
+    /// Treats items named exactly `name` as legendary: their quality is fixed at
+    /// `fixed_quality` and their sell_in never changes.
+    pub fn register_legendary(&mut self, name: impl Into<String>, fixed_quality: i32) {
+        self.rules
+            .factory
+            .register_legendary(name.into(), fixed_quality);
+    }
+
+    /// Records a snapshot of the items before every update from now on, so updates can
+    /// be reverted with [`GildedRose::undo`].
+    pub fn enable_history(&mut self) {
+        self.history.get_or_insert_with(Vec::new);
+    }
+
+    pub fn history_len(&self) -> usize {
+        self.history.as_ref().map_or(0, Vec::len)
+    }
+
+    /// Restores the items and the day as they were before the most recent update.
+    /// Returns `false` when there is nothing to undo.
+    pub fn undo(&mut self) -> bool {
+        match self.history.as_mut().and_then(Vec::pop) {
+            Some(items) => {
+                self.items = items;
+                self.day = self.day.saturating_sub(self.rules.config.time_step.days);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns a copy of the items to hand back to [`GildedRose::restore`] later.
+    pub fn checkpoint(&self) -> Vec<Item> {
+        self.items.clone()
+    }
+
+    /// Swaps in the items from `checkpoint`. The day counter and history are left alone.
+    pub fn restore(&mut self, checkpoint: Vec<Item>) {
+        self.items = checkpoint;
+    }
+
+    /// Days elapsed since the shop was created. Each update adds the configured
+    /// [`TimeStep`].
+    pub fn current_day(&self) -> u32 {
+        self.day
+    }
+
+    fn start_day(&mut self) {
+        if let Some(history) = self.history.as_mut() {
+            history.push(self.items.clone());
+        }
+        self.day = self.day.saturating_add(self.rules.config.time_step.days);
+    }
+
+    pub fn update_quality(&mut self) {
+        self.update_each(|_| (), |_, _, _| {});
+    }
+
+    /// Updates like [`GildedRose::update_quality`], calling `on_update` with each item
+    /// before and after its update.
+    pub fn update_quality_with<F: FnMut(&Item, &Item)>(&mut self, mut on_update: F) {
+        self.update_each(Item::clone, |before, after, _| on_update(&before, after));
+    }
+
+    /// Updates every item in place, handing `on_update` the `snapshot` taken before the
+    /// update, the updated item and whether its quality was clamped.
+    fn update_each<B, S, F>(&mut self, snapshot: S, mut on_update: F)
+    where
+        S: Fn(&Item) -> B,
+        F: FnMut(B, &Item, bool),
+    {
+        self.start_day();
+        for item in &mut self.items {
+            let state = self.rules.next_state(item);
+            #[cfg(feature = "log")]
+            self.rules.log_update(item, &state);
+            let before = snapshot(item);
+            state.write_to(item);
+            on_update(before, item, state.clamped);
+        }
+    }
+
+    /// Runs one update and returns whether any item's quality or sell_in changed.
+    pub fn step(&mut self) -> bool {
+        let mut changed = false;
+        self.update_each(ItemState::of, |before, after, _| {
+            changed |= before.quality != after.quality || before.sell_in != after.sell_in
+        });
+        changed
+    }
+
+    /// Runs one update and returns how many non-legendary items dropped to quality 0
+    /// during it. Items that were already at 0 are not counted.
+    pub fn update_and_count_depleted(&mut self) -> usize {
+        // legendary status is decided up front, the update borrows the whole shop
+        let rules = &self.rules;
+        let legendary: Vec<bool> = self
+            .items
+            .iter()
+            .map(|item| rules.is_legendary(item))
+            .collect();
+        let mut legendary = legendary.into_iter();
+        let mut depleted = 0;
+        self.update_each(ItemState::of, |before, after, _| {
+            if legendary.next() == Some(false) && before.quality != 0 && after.quality == 0 {
+                depleted += 1;
+            }
+        });
+        depleted
+    }
+
+    /// Whether an update would leave every item's quality and sell_in as they are.
+    pub fn is_stable(&self) -> bool {
+        self.items.iter().all(|item| {
+            let next = self.rules.next_state(item);
+            next.quality == item.quality && next.sell_in == item.sell_in
+        })
+    }
+
+    pub fn update_quality_with_report(&mut self) -> Vec<ItemChange> {
+        let mut changes = Vec::with_capacity(self.items.len());
+        self.update_each(ItemState::of, |before, after, clamped| {
+            changes.push(ItemChange {
+                name: after.name.clone(),
+                quality_delta: after.quality.saturating_sub(before.quality),
+                sell_in_delta: after.sell_in.saturating_sub(before.sell_in),
+                clamped,
+            })
+        });
+        changes
+    }
+
+    /// Updates like [`GildedRose::update_quality`], but leaves legendary items whose
+    /// quality differs from their fixed quality untouched and reports them instead of
+    /// correcting them.
+    pub fn update_quality_checked(&mut self) -> Result<(), Vec<ItemError>> {
+        self.start_day();
+        let mut errors = Vec::new();
+        for item in self.items.iter_mut().filter(|item| !item.skips_updates()) {
+            let calculator = self.rules.factory.create_calculator(item);
+            match calculator.fixed_quality() {
+                Some(fixed_quality)
+                    if !self
+                        .rules
+                        .config
+                        .legendary
+                        .accepts(item.quality, fixed_quality) =>
+                {
+                    errors.push(ItemError::InvalidLegendaryQuality {
+                        name: item.name.clone(),
+                        quality: item.quality,
+                    });
+                }
+                _ => {
+                    let state = ItemState::of(item).advance(calculator.as_ref(), &self.rules);
+                    #[cfg(feature = "log")]
+                    self.rules.log_update(item, &state);
+                    state.write_to(item)
+                }
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Reports every inconsistent item at once, without changing anything. Legendary
+    /// sell_in is only checked when history is enabled, see
+    /// [`GildedRose::validate_against`] to check it against a checkpoint instead.
+    ///
+    /// Reports legendaries outside the [`LegendaryConfig`] tolerance of their fixed
+    /// quality, other items outside their kind's quality range, and, with history,
+    /// legendaries whose sell_in differs from the snapshot taken before the latest
+    /// update.
+    pub fn validate(&self) -> Vec<ItemError> {
+        let latest = self.history.as_ref().and_then(|history| history.last());
+        self.audit(latest.map(Vec::as_slice))
+    }
+
+    /// Validates like [`GildedRose::validate`], comparing legendary sell_in with
+    /// `checkpoint` instead, such as one taken with [`GildedRose::checkpoint`].
+    pub fn validate_against(&self, checkpoint: &[Item]) -> Vec<ItemError> {
+        self.audit(Some(checkpoint))
+    }
+
+    /// Checks every item's quality and, given an earlier snapshot, the sell_in of
+    /// legendary items, pairing them with the snapshot by name in order.
+    fn audit(&self, earlier: Option<&[Item]>) -> Vec<ItemError> {
+        let mut unmatched: Vec<&Item> = earlier.unwrap_or(&[]).iter().collect();
+        let mut errors = Vec::new();
+        for item in &self.items {
+            if let Err(error) = self.rules.check_quality(item, item.quality) {
+                errors.push(error);
+            }
+            if !self.rules.is_legendary(item) {
+                continue;
+            }
+            let expected = match unmatched.iter().position(|old| old.name == item.name) {
+                Some(position) => unmatched.remove(position).sell_in,
+                None => continue,
+            };
+            if item.sell_in != expected {
+                errors.push(ItemError::LegendarySellInChanged {
+                    name: item.name.clone(),
+                    sell_in: item.sell_in,
+                    expected,
+                });
+            }
+        }
+        errors
+    }
+
+    pub fn preview_update(&self) -> Vec<Item> {
+        self.items
+            .iter()
+            .map(|item| self.rules.next_item(item))
+            .collect()
+    }
+
+    /// Returns the items after each of the next `days` updates, starting with the current
+    /// state as day 0, without changing this shop.
+    pub fn simulate(&self, days: u32) -> Vec<Vec<Item>> {
+        let mut simulation = self.detached(self.items.clone());
+        let mut snapshots = Vec::with_capacity(days as usize + 1);
+        snapshots.push(simulation.items.clone());
+        for _ in 0..days {
+            simulation.update_quality();
+            snapshots.push(simulation.items.clone());
+        }
+        snapshots
+    }
+
+    /// Sets the quality of the item at `index` if it is valid for the item's kind under
+    /// this shop's configuration, policy and registrations. `None` if there is no such
+    /// item.
+    pub fn set_quality(&mut self, index: usize, quality: i32) -> Option<Result<(), ItemError>> {
+        let item = self.items.get_mut(index)?;
+        Some(
+            self.rules
+                .check_quality(item, quality)
+                .map(|()| item.quality = quality),
+        )
+    }
+
+    /// Sets the sell_in of the item at `index` like [`Item::set_sell_in`], under this
+    /// shop's registrations and [`QualityConfig::min_sell_in`]. `None` if there is no such
+    /// item.
+    pub fn set_sell_in(&mut self, index: usize, sell_in: i32) -> Option<Result<(), ItemError>> {
+        let item = self.items.get_mut(index)?;
+        Some(
+            self.rules
+                .check_sell_in(item, sell_in)
+                .map(|()| item.sell_in = sell_in),
+        )
+    }
+
+    /// Returns the quality of the item at `index` after `days` updates under this shop's
+    /// rules, or `None` if there is no such item.
+    pub fn quality_after(&self, index: usize, days: u32) -> Option<i32> {
+        let mut item = self.items.get(index)?.clone();
+        for _ in 0..days {
+            item = self.rules.next_item(&item);
+        }
+        Some(item.quality)
+    }
+
+    /// Returns a copy of this shop advanced one day, leaving this shop untouched. The copy
+    /// keeps the rules and day counter but starts without history.
+    pub fn updated(&self) -> GildedRose {
+        let mut updated = self.detached(self.items.clone());
+        updated.update_quality();
+        updated
+    }
+
+    /// Returns a copy of this shop with every item replaced by `f(item)`, leaving this
+    /// shop untouched.
+    pub fn map_items<F: FnMut(&Item) -> Item>(&self, f: F) -> GildedRose {
+        self.detached(self.items.iter().map(f).collect())
+    }
+
+    /// A shop with the same rules and day as this one, holding `items` and no history.
+    fn detached(&self, items: Vec<Item>) -> GildedRose {
+        GildedRose {
+            items,
+            rules: self.rules.clone(),
+            history: None,
+            day: self.day,
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    pub fn par_update_quality(&mut self) {
+        use rayon::prelude::*;
+
+        self.start_day();
+        let rules = &self.rules;
+        self.items.par_iter_mut().for_each(|item| {
+            let state = rules.next_state(item);
+            #[cfg(feature = "log")]
+            rules.log_update(item, &state);
+            state.write_to(item)
+        });
+    }
+
+    /// Runs `days` updates, which is `days` days with the default [`TimeStep`].
+    pub fn advance_days(&mut self, days: u32) {
+        for _ in 0..days {
+            self.update_quality();
+        }
+    }
+
+    /// Advances `days` days like [`GildedRose::advance_days`], returning the changes of
+    /// each day.
+    pub fn run(&mut self, days: u32) -> Vec<Vec<ItemChange>> {
+        (0..days)
+            .map(|_| self.update_quality_with_report())
+            .collect()
+    }
+
+    /// Updates until [`GildedRose::current_day`] reaches `target_day`, or the first day
+    /// past it when the [`TimeStep`] does not land on it. Does nothing if the shop is
+    /// already at or past it, or if updates do not move time forward.
+    pub fn update_until(&mut self, target_day: u32) {
+        let step = self.rules.config.time_step.days;
+        if step == 0 {
+            return;
+        }
+        self.advance_days(target_day.saturating_sub(self.day).div_ceil(step));
+    }
+}
+
+impl Display for GildedRose {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (name_header, sell_in_header, quality_header) = ("Name", "SellIn", "Quality");
+        let name_width = self
+            .items
+            .iter()
+            .map(|item| item.display_name().chars().count())
+            .fold(name_header.len(), usize::max);
+        let sell_in_width = self
+            .items
+            .iter()
+            .map(|item| item.sell_in.to_string().len())
+            .fold(sell_in_header.len(), usize::max);
+        let quality_width = self
+            .items
+            .iter()
+            .map(|item| item.quality.to_string().len())
+            .fold(quality_header.len(), usize::max);
+
+        writeln!(
+            f,
+            "{:<name_width$}  {:>sell_in_width$}  {:>quality_width$}",
+            name_header,
+            sell_in_header,
+            quality_header,
+            name_width = name_width,
+            sell_in_width = sell_in_width,
+            quality_width = quality_width,
+        )?;
+        for item in &self.items {
+            writeln!(
+                f,
+                "{:<name_width$}  {:>sell_in_width$}  {:>quality_width$}",
+                item.display_name(),
+                item.sell_in,
+                item.quality,
+                name_width = name_width,
+                sell_in_width = sell_in_width,
+                quality_width = quality_width,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl IntoIterator for GildedRose {
+    type Item = Item;
+    type IntoIter = std::vec::IntoIter<Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a GildedRose {
+    type Item = &'a Item;
+    type IntoIter = std::slice::Iter<'a, Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut GildedRose {
+    type Item = &'a mut Item;
+    type IntoIter = std::slice::IterMut<'a, Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.iter_mut()
+    }
+}
+
+impl Default for GildedRose {
+    fn default() -> GildedRose {
+        GildedRose::new(Vec::new())
+    }
+}
+
+impl FromIterator<Item> for GildedRose {
+    fn from_iter<I: IntoIterator<Item = Item>>(iter: I) -> GildedRose {
+        GildedRose::new(iter.into_iter().collect())
+    }
+}
+
+impl Extend<Item> for GildedRose {
+    fn extend<I: IntoIterator<Item = Item>>(&mut self, iter: I) {
+        self.items.extend(iter);
+    }
+}
+
+struct DefaultQualityIncrement;
+
+impl DefaultQualityIncrement {
+    fn get(sell_in: i32) -> i32 {
+        if sell_in < 1 {
+            EXPIRED_QUALITY_INCREMENT
+        } else {
+            QUALITY_INCREMENT
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod item_kind {
+        use crate::gildedrose::{GildedRose, Item, ItemKind};
+
+        #[test]
+        fn given_special_names_when_classified_then_matches_kind() {
+            assert_eq!(ItemKind::from_name("Aged Brie"), ItemKind::AgedBrie);
+            assert_eq!(
+                ItemKind::from_name("Backstage passes to a TAFKAL80ETC concert"),
+                ItemKind::BackstagePasses
+            );
+            assert_eq!(
+                ItemKind::from_name("Sulfuras, Hand of Ragnaros"),
+                ItemKind::Sulfuras
+            );
+            assert_eq!(
+                ItemKind::from_name("Conjured Mana Cake"),
+                ItemKind::Conjured
+            );
+            assert_eq!(
+                ItemKind::from_name("Cheese wheel, Gruyère"),
+                ItemKind::Ripening
+            );
+            assert_eq!(ItemKind::from_name("+5 Dexterity Vest"), ItemKind::Normal);
+        }
+
+        #[test]
+        fn given_mixed_case_names_when_classified_then_ignores_case() {
+            assert_eq!(ItemKind::from_name("aged BRIE"), ItemKind::AgedBrie);
+            assert_eq!(
+                ItemKind::from_name("backstage passes to a TAFKAL80ETC concert"),
+                ItemKind::BackstagePasses
+            );
+            assert_eq!(
+                ItemKind::from_name("BACKSTAGE PASSES"),
+                ItemKind::BackstagePasses
+            );
+            assert_eq!(
+                ItemKind::from_name("sulfuras, hand of ragnaros"),
+                ItemKind::Sulfuras
+            );
+            assert_eq!(
+                ItemKind::from_name("conjured mana cake"),
+                ItemKind::Conjured
+            );
+            assert_eq!(ItemKind::from_name("CHEESE Wheel"), ItemKind::Ripening);
+            assert_eq!(ItemKind::from_name("+5 DEXTERITY vest"), ItemKind::Normal);
+        }
+
+        #[test]
+        fn given_ambiguous_names_when_classified_then_is_normal() {
+            assert_eq!(ItemKind::from_name("My Sulfuras Replica"), ItemKind::Normal);
+            assert_eq!(ItemKind::from_name("Aged Brie Crumbs"), ItemKind::Normal);
+            assert_eq!(ItemKind::from_name("Not Aged Brie"), ItemKind::Normal);
+            assert_eq!(
+                ItemKind::from_name("Old Backstage passes"),
+                ItemKind::Normal
+            );
+            assert_eq!(ItemKind::from_name("Unconjured Cake"), ItemKind::Normal);
+        }
+
+        #[test]
+        fn given_multibyte_names_when_classified_then_prefixes_do_not_split_characters() {
+            assert_eq!(ItemKind::from_name("Sulfurä"), ItemKind::Normal);
+            assert_eq!(ItemKind::from_name("Conjuréd Cake"), ItemKind::Normal);
+            assert_eq!(ItemKind::from_name("CONJURED Crème"), ItemKind::Conjured);
+        }
+
+        #[test]
+        fn given_ambiguous_name_when_updated_then_decays_like_normal_item() {
+            // given
+            let item = Item::new("My Sulfuras Replica", 10, 20);
+            let mut rose = GildedRose::new(vec![item]);
+
+            // when
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0].quality, 19);
+            assert_eq!(rose.items[0].sell_in, 9);
+        }
+
+        #[test]
+        fn given_explicit_kind_when_updated_then_overrides_name() {
+            // given
+            let mut item = Item::new("Custom Sword", 10, 80);
+            item.kind = Some(ItemKind::Sulfuras);
+            let mut rose = GildedRose::new(vec![item]);
+
+            // when
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0].quality, 80);
+            assert_eq!(rose.items[0].sell_in, 10);
+        }
+    }
+
+    mod conjured {
+        use crate::gildedrose::{GildedRose, Item};
+
+        #[test]
+        fn when_updated_then_decreases_in_quality_by_two() {
+            // given
+            let item = Item::new("Conjured Cookie", 20, 50);
+            let mut rose = GildedRose::new(vec![item]);
+
+            // when
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0].quality, 48);
+        }
+
+        #[test]
+        fn given_after_sale_when_updated_then_decreases_in_quality_by_four() {
+            // given
+            let item = Item::new("Conjured Cookie", -4, 50);
+            let mut rose = GildedRose::new(vec![item]);
+
+            // when
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0].quality, 46);
+        }
+
+        #[test]
+        fn when_updated_then_sell_in_decreases() {
+            // given
+            let item = Item::new("Conjured Cookie", -4, 50);
+            let mut rose = GildedRose::new(vec![item]);
+
+            // when
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0].sell_in, -5);
+        }
+
+        #[test]
+        fn given_zero_quality_when_updated_then_quality_is_zero() {
+            // given
+            let item = Item::new("Conjured", 2, 0);
+            let mut rose = GildedRose::new(vec![item]);
+
+            // when
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0].quality, 0);
+        }
+
+        #[test]
+        fn given_after_sale_and_low_quality_when_updated_then_quality_is_zero() {
+            // given
+            let item = Item::new("Conjured Mana Cake", 0, 3);
+            let mut rose = GildedRose::new(vec![item]);
+
+            // when
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0].quality, 0);
+        }
+    }
+
+    mod ragnaros {
+        use crate::gildedrose::{GildedRose, Item};
+
+        #[test]
+        fn when_updated_then_does_not_alter_quality() {
+            // given
+            let item = Item::new("Sulfuras", 20, 80);
+            let mut rose = GildedRose::new(vec![item]);
+
+            // when
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0].quality, 80);
+        }
+
+        #[test]
+        fn when_updated_then_does_not_alter_sell_in() {
+            // given
+            let item = Item::new("Sulfuras", 20, 80);
+            let mut rose = GildedRose::new(vec![item]);
+
+            // when
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0].sell_in, 20);
+        }
+
+        #[test]
+        fn when_updated_then_equals_snapshot() {
+            // given
+            let item = Item::new("Sulfuras, Hand of Ragnaros", -1, 80);
+            let before = item.clone();
+            let mut rose = GildedRose::new(vec![item]);
+
+            // when
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0], before);
+        }
+    }
+
+    mod normal_item {
+        use crate::gildedrose::{GildedRose, Item};
+
+        #[test]
+        fn when_updated_then_decreases_in_quality() {
+            // given
+            let item = Item::new("Item", 10, 50);
+            let mut rose = GildedRose::new(vec![item]);
+
+            // when
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0].quality, 49);
+        }
+
+        #[test]
+        fn when_updated_then_sell_in_decreases() {
+            // given
+            let item = Item::new("Item", 10, 50);
+            let mut rose = GildedRose::new(vec![item]);
+
+            // when
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0].sell_in, 9);
+        }
+
+        #[test]
+        fn given_zero_quality_when_updated_then_quality_is_zero() {
+            // given
+            let item = Item::new("Item", 10, 0);
+            let mut rose = GildedRose::new(vec![item]);
+
+            // when
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0].quality, 0);
+        }
+
+        #[test]
+        fn given_surpassed_sell_date_when_updated_quality_decreases_by_two() {
+            // given
+            let item = Item::new("Item", 0, 4);
+            let mut rose = GildedRose::new(vec![item]);
+
+            // when
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0].quality, 2);
+        }
+    }
+
+    mod aged_brie {
+        use crate::gildedrose::{GildedRose, Item, StandardPolicy};
+
+        #[test]
+        fn when_updated_then_sell_in_decreases() {
+            // given
+            let item = Item::new("Aged Brie", 2, 10);
+            let mut rose = GildedRose::new(vec![item]);
+
+            // when
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0].sell_in, 1);
+        }
+
+        #[test]
+        fn when_updated_then_increases_in_quality() {
+            // given
+            let item = Item::new("Aged Brie", 2, 10);
+            let mut rose = GildedRose::new(vec![item]);
+
+            // when
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0].quality, 11);
+        }
+
+        #[test]
+        fn given_sell_in_date_when_updated_then_increases_in_quality_by_two() {
+            // given
+            let item = Item::new("Aged Brie", 0, 10);
+            let mut rose = GildedRose::new(vec![item]);
+
+            // when
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0].quality, 12);
+        }
+
+        #[test]
+        fn given_quality_of_50_when_updated_then_does_not_alter_quality() {
+            // given
+            let item = Item::new("Aged Brie", 20, 50);
+            let mut rose = GildedRose::new(vec![item]);
+
+            // when
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0].quality, 50);
+        }
+
+        #[test]
+        fn given_quality_above_50_when_updated_then_clamps_to_50() {
+            // given
+            let mut rose = GildedRose::new(vec![
+                Item::new("Aged Brie", 5, 60),
+                Item::new("Aged Brie", 5, 52),
+            ]);
+
+            // when
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0].quality, 50);
+            assert_eq!(rose.items[1].quality, 50);
+        }
+
+        #[test]
+        fn given_quality_of_49_when_updated_twice_then_reaches_50_and_stays() {
+            // given
+            let mut rose = GildedRose::new(vec![Item::new("Aged Brie", 5, 49)]);
+
+            // when
+            rose.update_quality();
+            let first = rose.items[0].quality;
+            rose.update_quality();
+
+            // then
+            assert_eq!(first, 50);
+            assert_eq!(rose.items[0].quality, 50);
+        }
+
+        #[test]
+        fn given_negative_quality_when_updated_then_lands_at_0() {
+            let mut rose = GildedRose::new(vec![Item::new("Aged Brie", 5, -5)]);
+
+            rose.update_quality();
+
+            assert_eq!(rose.items[0].quality, 0);
+        }
+
+        #[test]
+        fn given_wider_policy_when_updated_then_ages_like_normal_items_decay() {
+            // given
+            let policy = StandardPolicy {
+                min_quality: -10,
+                max_quality: 100,
+            };
+            let items = vec![
+                Item::new("Aged Brie", 5, 60),
+                Item::new("Item", 5, 60),
+                Item::new("Aged Brie", 5, -5),
+                Item::new("Item", 5, -5),
+            ];
+            let mut rose = GildedRose::with_policy(items, policy);
+
+            // when
+            rose.update_quality();
+
+            // then
+            let qualities: Vec<i32> = rose.iter().map(|item| item.quality).collect();
+            assert_eq!(qualities, vec![61, 59, -4, -6]);
+        }
+    }
+
+    mod backstage_passes {
+        use crate::gildedrose::{GildedRose, Item};
+
+        #[test]
+        fn when_updated_then_increases_in_quality() {
+            // given
+            let item = Item::new("Backstage passes", 11, 10);
+            let mut rose = GildedRose::new(vec![item]);
+
+            // when
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0].quality, 11);
+        }
+
+        #[test]
+        fn given_10_days_when_updated_then_increases_in_quality_by_two() {
+            // given
+            let item = Item::new("Backstage passes", 10, 10);
+            let mut rose = GildedRose::new(vec![item]);
+
+            // when
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0].quality, 12);
+        }
+
+        #[test]
+        fn given_5_days_when_updated_then_increases_in_quality_by_three() {
+            // given
+            let item = Item::new("Backstage passes", 5, 10);
+            let mut rose = GildedRose::new(vec![item]);
+
+            // when
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0].quality, 13);
+        }
+
+        #[test]
+        fn given_0_days_when_updated_quality_drops_to_zero() {
+            // given
+            let item = Item::new("Backstage passes", 0, 10);
+            let mut rose = GildedRose::new(vec![item]);
+
+            // when
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0].quality, 0);
+        }
+
+        #[test]
+        fn given_expired_pass_when_updated_three_times_then_stays_at_zero() {
+            // given
+            let item = Item::new("Backstage passes", -3, 0);
+            let mut rose = GildedRose::new(vec![item]);
+
+            // when
+            rose.advance_days(3);
+
+            // then
+            assert_eq!(rose.items[0], Item::new("Backstage passes", -6, 0));
+        }
+
+        #[test]
+        fn when_updated_then_sell_in_decreases() {
+            // given
+            let item = Item::new("Backstage passes", 2, 0);
+            let mut rose = GildedRose::new(vec![item]);
+
+            // when
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0].sell_in, 1);
+        }
+    }
+
+    mod advance_days {
+        use crate::gildedrose::{GildedRose, Item};
+
+        #[test]
+        fn given_zero_days_when_advanced_then_nothing_changes() {
+            // given
+            let item = Item::new("Item", 10, 20);
+            let mut rose = GildedRose::new(vec![item]);
+
+            // when
+            rose.advance_days(0);
+
+            // then
+            assert_eq!(rose.items[0].sell_in, 10);
+            assert_eq!(rose.items[0].quality, 20);
+        }
+
+        #[test]
+        fn when_advanced_then_matches_repeated_updates() {
+            // given
+            let items = || {
+                vec![
+                    Item::new("Item", 3, 10),
+                    Item::new("Aged Brie", 2, 45),
+                    Item::new("Sulfuras, Hand of Ragnaros", -1, 80),
+                    Item::new("Backstage passes to a TAFKAL80ETC concert", 12, 20),
+                    Item::new("Conjured Mana Cake", 3, 20),
+                ]
+            };
+            let mut advanced = GildedRose::new(items());
+            let mut updated = GildedRose::new(items());
+
+            // when
+            advanced.advance_days(7);
+            for _ in 0..7 {
+                updated.update_quality();
+            }
+
+            // then
+            assert_eq!(advanced.items, updated.items);
+        }
+
+        #[test]
+        fn given_backstage_pass_when_advanced_past_concert_then_quality_stays_zero() {
+            // given
+            let item = Item::new("Backstage passes to a TAFKAL80ETC concert", 3, 20);
+            let mut rose = GildedRose::new(vec![item]);
+
+            // when
+            rose.advance_days(4);
+
+            // then
+            assert_eq!(rose.items[0].quality, 0);
+
+            // when
+            rose.advance_days(3);
+
+            // then
+            assert_eq!(rose.items[0].quality, 0);
+            assert_eq!(rose.items[0].sell_in, -4);
+        }
+    }
+
+    mod report {
+        use crate::gildedrose::{GildedRose, Item, ItemChange};
+
+        #[test]
+        fn given_aged_brie_when_updated_then_reports_quality_increase() {
+            // given
+            let item = Item::new("Aged Brie", 2, 10);
+            let mut rose = GildedRose::new(vec![item]);
+
+            // when
+            let changes = rose.update_quality_with_report();
+
+            // then
+            assert_eq!(
+                changes,
+                vec![ItemChange {
+                    name: "Aged Brie".to_string(),
+                    quality_delta: 1,
+                    sell_in_delta: -1,
+                    clamped: false,
+                }]
+            );
+        }
+
+        #[test]
+        fn given_normal_item_when_updated_then_reports_quality_decrease() {
+            // given
+            let item = Item::new("Item", 10, 20);
+            let mut rose = GildedRose::new(vec![item]);
+
+            // when
+            let changes = rose.update_quality_with_report();
+
+            // then
+            assert_eq!(changes[0].quality_delta, -1);
+            assert_eq!(changes[0].sell_in_delta, -1);
+        }
+
+        #[test]
+        fn given_expired_backstage_pass_when_updated_then_reports_loss_of_all_quality() {
+            // given
+            let item = Item::new("Backstage passes", 0, 42);
+            let mut rose = GildedRose::new(vec![item]);
+
+            // when
+            let changes = rose.update_quality_with_report();
+
+            // then
+            assert_eq!(changes[0].quality_delta, -42);
+        }
+
+        #[test]
+        fn given_sulfuras_when_updated_then_reports_no_change() {
+            // given
+            let item = Item::new("Sulfuras, Hand of Ragnaros", 5, 80);
+            let mut rose = GildedRose::new(vec![item]);
+
+            // when
+            let changes = rose.update_quality_with_report();
+
+            // then
+            assert_eq!(changes[0].quality_delta, 0);
+            assert_eq!(changes[0].sell_in_delta, 0);
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    mod serde {
+        use crate::gildedrose::{GildedRose, Item};
+
+        #[test]
+        fn when_round_tripped_through_json_then_items_are_equal() {
+            // given
+            let rose = GildedRose::new(vec![
+                Item::new("Item", 10, 20),
+                Item::new("Aged Brie", 2, 0),
+                Item::new("Sulfuras, Hand of Ragnaros", -1, 80),
+                Item::new("Backstage passes to a TAFKAL80ETC concert", 15, 20),
+                Item::new("Conjured Mana Cake", 3, 6),
+            ]);
+
+            // when
+            let json = serde_json::to_string(&rose).unwrap();
+            let restored: GildedRose = serde_json::from_str(&json).unwrap();
+
+            // then
+            assert_eq!(restored.items, rose.items);
+        }
+
+        #[test]
+        fn when_serialized_then_maps_fields_directly() {
+            // given
+            let item = Item::new("Aged Brie", 2, 10);
+
+            // when
+            let json = serde_json::to_string(&item).unwrap();
+
+            // then
+            assert_eq!(json, r#"{"name":"Aged Brie","sell_in":2,"quality":10}"#);
+        }
+    }
+
+    mod quality_config {
+        use crate::gildedrose::{GildedRose, Item, QualityConfig};
+
+        #[test]
+        fn given_raised_cap_when_updated_then_aged_brie_climbs_past_50() {
+            // given
+            let item = Item::new("Aged Brie", 10, 50);
+            let config = QualityConfig {
+                max_quality: 100,
+                ..QualityConfig::default()
+            };
+            let mut rose = GildedRose::with_config(vec![item], config);
+
+            // when
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0].quality, 51);
+        }
+
+        #[test]
+        fn given_raised_cap_when_updated_then_backstage_pass_stops_at_cap() {
+            // given
+            let item = Item::new("Backstage passes", 5, 98);
+            let config = QualityConfig {
+                max_quality: 100,
+                ..QualityConfig::default()
+            };
+            let mut rose = GildedRose::with_config(vec![item], config);
+
+            // when
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0].quality, 100);
+        }
+
+        #[test]
+        fn given_raised_floor_when_updated_then_normal_item_stops_at_floor() {
+            // given
+            let item = Item::new("Item", 0, 6);
+            let config = QualityConfig {
+                min_quality: 5,
+                ..QualityConfig::default()
+            };
+            let mut rose = GildedRose::with_config(vec![item], config);
+
+            // when
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0].quality, 5);
+        }
+
+        #[test]
+        fn given_raised_cap_when_updated_then_sulfuras_stays_at_80() {
+            // given
+            let item = Item::new("Sulfuras, Hand of Ragnaros", 5, 80);
+            let config = QualityConfig {
+                max_quality: 100,
+                ..QualityConfig::default()
+            };
+            let mut rose = GildedRose::with_config(vec![item], config);
+
+            // when
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0].quality, 80);
+        }
+    }
+
+    mod backstage_config {
+        use crate::gildedrose::{BackstageConfig, GildedRose, Item, QualityConfig};
+
+        fn rose_with_tiers(sell_in: i32, tier2_days: i32, tier3_days: i32) -> GildedRose {
+            let item = Item::new("Backstage passes", sell_in, 10);
+            let config = QualityConfig {
+                backstage: BackstageConfig {
+                    tier2_days,
+                    tier3_days,
+                },
+                ..QualityConfig::default()
+            };
+            GildedRose::with_config(vec![item], config)
+        }
+
+        #[test]
+        fn given_tier2_at_15_days_when_updated_with_15_days_then_increases_by_two() {
+            // given
+            let mut rose = rose_with_tiers(15, 15, 5);
+
+            // when
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0].quality, 12);
+        }
+
+        #[test]
+        fn given_tier2_at_15_days_when_updated_with_16_days_then_increases_by_one() {
+            // given
+            let mut rose = rose_with_tiers(16, 15, 5);
+
+            // when
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0].quality, 11);
+        }
+
+        #[test]
+        fn given_tier3_at_8_days_when_updated_with_8_days_then_increases_by_three() {
+            // given
+            let mut rose = rose_with_tiers(8, 15, 8);
+
+            // when
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0].quality, 13);
+        }
+
+        #[test]
+        fn given_custom_tiers_when_updated_after_concert_then_quality_drops_to_zero() {
+            // given
+            let mut rose = rose_with_tiers(0, 15, 8);
+
+            // when
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0].quality, 0);
+        }
+    }
+
+    mod try_new {
+        use crate::gildedrose::{Item, ItemError};
+
+        #[test]
+        fn given_valid_quality_when_created_then_returns_item() {
+            assert_eq!(Item::try_new("Item", 5, 50), Ok(Item::new("Item", 5, 50)));
+        }
+
+        #[test]
+        fn given_quality_above_50_when_created_then_fails() {
+            assert_eq!(
+                Item::try_new("Item", 5, 999),
+                Err(ItemError::QualityTooHigh {
+                    name: "Item".to_string(),
+                    quality: 999,
+                    max: 50,
+                })
+            );
+        }
+
+        #[test]
+        fn given_negative_quality_when_created_then_fails() {
+            assert_eq!(
+                Item::try_new("Aged Brie", 5, -1),
+                Err(ItemError::QualityNegative {
+                    name: "Aged Brie".to_string(),
+                    quality: -1,
+                    min: 0,
+                })
+            );
+        }
+
+        #[test]
+        fn given_sulfuras_not_at_80_when_created_then_fails() {
+            assert_eq!(
+                Item::try_new("Sulfuras, Hand of Ragnaros", 0, 50),
+                Err(ItemError::InvalidLegendaryQuality {
+                    name: "Sulfuras, Hand of Ragnaros".to_string(),
+                    quality: 50,
+                })
+            );
+        }
+
+        #[test]
+        fn given_sulfuras_at_80_when_created_then_returns_item() {
+            assert!(Item::try_new("Sulfuras, Hand of Ragnaros", 0, 80).is_ok());
+        }
+    }
+
+    mod custom_calculator {
+        use crate::gildedrose::{
+            CalculateQuality, CalculateSellIn, Calculations, GildedRose, Item, QualityConfig,
+        };
+
+        struct Cheese;
+
+        impl CalculateQuality for Cheese {
+            fn calculate_new_quality(&self, sell_in: i32, quality: i32, _: &QualityConfig) -> i32 {
+                let increment = if sell_in < 1 { 2 } else { 1 };
+                (quality + increment).min(40)
+            }
+        }
+
+        impl CalculateSellIn for Cheese {}
+
+        impl Calculations for Cheese {}
+
+        fn is_cheese(name: &str) -> bool {
+            name.contains("Cheese")
+        }
+
+        fn cheese() -> Box<dyn Calculations> {
+            Box::new(Cheese)
+        }
+
+        #[test]
+        fn given_registered_cheese_when_updated_then_ripens_like_brie() {
+            // given
+            let item = Item::new("Cheese", 0, 10);
+            let mut rose = GildedRose::new(vec![item]);
+            rose.register_calculator(is_cheese, cheese);
+
+            // when
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0].quality, 12);
+            assert_eq!(rose.items[0].sell_in, -1);
+        }
+
+        #[test]
+        fn given_registered_cheese_when_updated_then_caps_at_40() {
+            // given
+            let item = Item::new("Cheese", 5, 40);
+            let mut rose = GildedRose::new(vec![item]);
+            rose.register_calculator(is_cheese, cheese);
+
+            // when
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0].quality, 40);
+        }
+
+        #[test]
+        fn given_custom_calculator_when_name_matches_built_in_then_custom_takes_precedence() {
+            // given
+            let item = Item::new("Conjured Cheese", 5, 10);
+            let mut rose = GildedRose::new(vec![item]);
+            rose.register_calculator(is_cheese, cheese);
+
+            // when
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0].quality, 11);
+        }
+
+        #[test]
+        fn given_custom_calculator_when_name_does_not_match_then_uses_built_in() {
+            // given
+            let item = Item::new("Aged Brie", 5, 10);
+            let mut rose = GildedRose::new(vec![item]);
+            rose.register_calculator(is_cheese, cheese);
+
+            // when
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0].quality, 11);
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    mod par_update {
+        use crate::gildedrose::{GildedRose, Item};
+
+        fn shuffled_inventory(len: usize) -> Vec<Item> {
+            let names = [
+                "Item",
+                "Aged Brie",
+                "Sulfuras, Hand of Ragnaros",
+                "Backstage passes to a TAFKAL80ETC concert",
+                "Conjured Mana Cake",
+            ];
+            let mut seed: u64 = 42;
+            let mut next = move || {
+                seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+                (seed >> 33) as i32
+            };
+            (0..len)
+                .map(|_| {
+                    let name = names[next() as usize % names.len()];
+                    Item::new(name, next() % 30 - 10, next() % 51)
+                })
+                .collect()
+        }
+
+        #[test]
+        fn when_updated_in_parallel_then_matches_sequential_update() {
+            // given
+            let items = shuffled_inventory(10_000);
+            let mut sequential = GildedRose::new(items.clone());
+            let mut parallel = GildedRose::new(items);
+
+            // when
+            for _ in 0..15 {
+                sequential.update_quality();
+                parallel.par_update_quality();
+            }
+
+            // then
+            assert_eq!(parallel.items, sequential.items);
+        }
+    }
+
+    mod update_order {
+        use crate::gildedrose::{GildedRose, Item};
+
+        #[test]
+        fn given_aged_brie_one_day_before_sell_date_when_updated_then_quality_uses_old_sell_in() {
+            // given
+            let item = Item::new("Aged Brie", 1, 10);
+            let mut rose = GildedRose::new(vec![item]);
+
+            // when
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0].quality, 11);
+            assert_eq!(rose.items[0].sell_in, 0);
+        }
+
+        #[test]
+        fn given_backstage_pass_at_11_days_when_updated_then_quality_uses_old_sell_in() {
+            // given
+            let item = Item::new("Backstage passes", 11, 10);
+            let mut rose = GildedRose::new(vec![item]);
+
+            // when
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0].quality, 11);
+            assert_eq!(rose.items[0].sell_in, 10);
+        }
+
+        #[test]
+        fn given_backstage_pass_one_day_before_concert_when_updated_then_does_not_crash_early() {
+            // given
+            let item = Item::new("Backstage passes", 1, 10);
+            let mut rose = GildedRose::new(vec![item]);
+
+            // when
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0].quality, 13);
+            assert_eq!(rose.items[0].sell_in, 0);
+        }
+
+        #[test]
+        fn given_normal_item_on_sell_date_when_updated_then_drops_by_two_and_expires() {
+            // given
+            let item = Item::new("Item", 0, 10);
+            let mut rose = GildedRose::new(vec![item]);
+
+            // when
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0].quality, 8);
+            assert_eq!(rose.items[0].sell_in, -1);
+        }
+    }
+
+    mod iteration {
+        use crate::gildedrose::{GildedRose, Item};
+
+        #[test]
+        fn when_iterated_by_reference_then_yields_items_in_order() {
+            // given
+            let rose = GildedRose::new(vec![Item::new("Item", 1, 2), Item::new("Aged Brie", 3, 4)]);
+
+            // when
+            let mut names = Vec::new();
+            for item in &rose {
+                names.push(item.name.as_str());
+            }
+
+            // then
+            assert_eq!(names, vec!["Item", "Aged Brie"]);
+        }
+
+        #[test]
+        fn when_iterated_mutably_then_changes_items() {
+            // given
+            let mut rose = GildedRose::new(vec![Item::new("Item", 1, 2)]);
+
+            // when
+            for item in rose.iter_mut() {
+                item.quality = 10;
+            }
+
+            // then
+            assert_eq!(rose.iter().next().unwrap().quality, 10);
+        }
+
+        #[test]
+        fn when_consumed_then_yields_owned_items() {
+            // given
+            let rose = GildedRose::new(vec![Item::new("Item", 1, 2)]);
+
+            // when
+            let items: Vec<Item> = rose.into_iter().collect();
+
+            // then
+            assert_eq!(items, vec![Item::new("Item", 1, 2)]);
+        }
+    }
+
+    mod display {
+        use crate::gildedrose::{GildedRose, Item, ItemVerbose};
+
+        #[test]
+        fn given_item_when_displayed_then_uses_compact_format() {
+            assert_eq!(
+                Item::new("Aged Brie", 10, 20).to_string(),
+                "Aged Brie, 10, 20"
+            );
+        }
+
+        #[test]
+        fn given_item_when_displayed_verbosely_then_includes_kind() {
+            // given
+            let item = Item::new("Aged Brie", 10, 20);
+
+            // when
+            let verbose = item.display_verbose().to_string();
+
+            // then
+            assert_eq!(verbose, "Aged Brie [AgedBrie] sell_in=10 quality=20");
+            assert_eq!(ItemVerbose(&item).to_string(), verbose);
+        }
+
+        #[test]
+        fn when_displayed_then_renders_aligned_table() {
+            // given
+            let rose = GildedRose::new(vec![
+                Item::new("Sulfuras, Hand of Ragnaros", -1, 80),
+                Item::new("Aged Brie", 2, 0),
+            ]);
+
+            // when
+            let table = rose.to_string();
+
+            // then
+            assert_eq!(
+                table,
+                "Name                        SellIn  Quality\n\
+                 Sulfuras, Hand of Ragnaros      -1       80\n\
+                 Aged Brie                        2        0\n"
+            );
+        }
+
+        #[test]
+        fn given_empty_shop_when_displayed_then_renders_header_only() {
+            assert_eq!(
+                GildedRose::new(vec![]).to_string(),
+                "Name  SellIn  Quality\n"
+            );
+        }
+    }
+
+    mod preview {
+        use crate::gildedrose::{GildedRose, Item};
+
+        #[test]
+        fn when_previewed_then_returns_updated_items_without_mutating() {
+            // given
+            let items = vec![
+                Item::new("Item", 0, 10),
+                Item::new("Aged Brie", 2, 10),
+                Item::new("Sulfuras, Hand of Ragnaros", -1, 80),
+            ];
+            let rose = GildedRose::new(items.clone());
+
+            // when
+            let preview = rose.preview_update();
+
+            // then
+            assert_eq!(
+                preview,
+                vec![
+                    Item::new("Item", -1, 8),
+                    Item::new("Aged Brie", 1, 11),
+                    Item::new("Sulfuras, Hand of Ragnaros", -1, 80),
+                ]
+            );
+            assert_eq!(rose.items, items);
+        }
+
+        #[test]
+        fn when_previewed_then_matches_actual_update() {
+            // given
+            let mut rose = GildedRose::new(vec![
+                Item::new("Backstage passes", 1, 48),
+                Item::new("Conjured Mana Cake", 0, 5),
+            ]);
+
+            // when
+            let preview = rose.preview_update();
+            rose.update_quality();
+
+            // then
+            assert_eq!(preview, rose.items);
+        }
+    }
+
+    mod quality_policy {
+        use crate::gildedrose::{GildedRose, Item, QualityPolicy, StandardPolicy};
+
+        struct DebtPolicy;
+
+        impl QualityPolicy for DebtPolicy {
+            fn clamp(&self, raw: i32) -> i32 {
+                raw.clamp(-10, 50)
+            }
+        }
+
+        #[test]
+        fn given_standard_policy_when_clamped_then_stays_within_0_and_50() {
+            let policy = StandardPolicy::default();
+
+            assert_eq!(policy.clamp(-3), 0);
+            assert_eq!(policy.clamp(25), 25);
+            assert_eq!(policy.clamp(51), 50);
+        }
+
+        #[test]
+        fn given_debt_policy_when_updated_then_normal_item_goes_negative() {
+            // given
+            let item = Item::new("Item", 0, 1);
+            let mut rose = GildedRose::with_policy(vec![item], DebtPolicy);
+
+            // when
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0].quality, -1);
+        }
+
+        #[test]
+        fn given_debt_policy_when_updated_repeatedly_then_stops_at_minus_10() {
+            // given
+            let item = Item::new("Conjured Mana Cake", 0, 0);
+            let mut rose = GildedRose::with_policy(vec![item], DebtPolicy);
+
+            // when
+            rose.advance_days(5);
+
+            // then
+            assert_eq!(rose.items[0].quality, -10);
+        }
+
+        #[test]
+        fn given_debt_policy_when_updated_then_aged_brie_still_caps_at_50() {
+            // given
+            let item = Item::new("Aged Brie", 0, 49);
+            let mut rose = GildedRose::with_policy(vec![item], DebtPolicy);
+
+            // when
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0].quality, 50);
+        }
+
+        #[test]
+        fn given_debt_policy_when_updated_then_sulfuras_is_exempt() {
+            // given
+            let item = Item::new("Sulfuras, Hand of Ragnaros", 0, 80);
+            let mut rose = GildedRose::with_policy(vec![item], DebtPolicy);
+
+            // when
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0].quality, 80);
+        }
+    }
+
+    mod expired {
+        use crate::gildedrose::{GildedRose, Item};
+
+        fn rose() -> GildedRose {
+            GildedRose::new(vec![
+                Item::new("Item", -1, 10),
+                Item::new("Elixir", 0, 10),
+                Item::new("Vest", -3, 0),
+                Item::new("Aged Brie", -2, 50),
+                Item::new("Sulfuras, Hand of Ragnaros", -5, 80),
+            ])
+        }
+
+        #[test]
+        fn when_counted_then_excludes_sulfuras_and_unexpired_items() {
+            assert_eq!(rose().count_expired(), 3);
+        }
+
+        #[test]
+        fn when_listed_then_returns_expired_items_in_order() {
+            // given
+            let rose = rose();
+
+            // when
+            let names: Vec<&str> = rose
+                .expired_items()
+                .into_iter()
+                .map(|item| item.name.as_str())
+                .collect();
+
+            // then
+            assert_eq!(names, vec!["Item", "Vest", "Aged Brie"]);
+        }
+
+        #[test]
+        fn given_empty_shop_when_counted_then_is_zero() {
+            assert_eq!(GildedRose::new(vec![]).count_expired(), 0);
+        }
+    }
+
+    mod aggregates {
+        use crate::gildedrose::{GildedRose, Item};
+
+        fn rose() -> GildedRose {
+            GildedRose::new(vec![
+                Item::new("Item", 10, 20),
+                Item::new("Aged Brie", 2, 50),
+                Item::new("Sulfuras, Hand of Ragnaros", 0, 80),
+                Item::new("Elixir", 5, 10),
+            ])
+        }
+
+        #[test]
+        fn when_totalled_then_sums_all_qualities() {
+            assert_eq!(rose().total_quality(), 160);
+        }
+
+        #[test]
+        fn when_averaged_then_divides_by_item_count() {
+            assert_eq!(rose().average_quality(), Some(40.0));
+        }
+
+        #[test]
+        fn when_sell_in_summed_then_adds_all_sell_ins() {
+            assert_eq!(rose().sum_sell_in(), 17);
+        }
+
+        #[test]
+        fn given_100k_items_at_i32_max_when_summed_then_does_not_overflow() {
+            // given
+            let count = 100_000;
+            let rose: GildedRose = (0..count)
+                .map(|_| Item::new("Item", i32::MAX, i32::MAX))
+                .collect();
+
+            // when
+            let total_quality = rose.total_quality();
+            let total_sell_in = rose.sum_sell_in();
+
+            // then
+            assert_eq!(total_quality, i64::from(i32::MAX) * count);
+            assert_eq!(total_sell_in, i64::from(i32::MAX) * count);
+        }
+
+        #[test]
+        fn when_highest_queried_then_picks_sulfuras() {
+            assert_eq!(
+                rose().highest_quality_item().map(|item| item.name.as_str()),
+                Some("Sulfuras, Hand of Ragnaros")
+            );
+        }
+
+        #[test]
+        fn given_tied_qualities_when_highest_queried_then_picks_first() {
+            // given
+            let rose = GildedRose::new(vec![Item::new("First", 1, 30), Item::new("Second", 1, 30)]);
+
+            // when
+            let highest = rose.highest_quality_item();
+
+            // then
+            assert_eq!(highest.map(|item| item.name.as_str()), Some("First"));
+        }
+
+        #[test]
+        fn given_empty_shop_when_aggregated_then_returns_nothing() {
+            // given
+            let rose = GildedRose::new(vec![]);
+
+            // then
+            assert_eq!(rose.total_quality(), 0);
+            assert_eq!(rose.average_quality(), None);
+            assert_eq!(rose.highest_quality_item(), None);
+        }
+    }
+
+    mod overflow {
+        use crate::gildedrose::{GildedRose, Item, QualityConfig, QualityPolicy};
+
+        struct Unbounded;
+
+        impl QualityPolicy for Unbounded {
+            fn clamp(&self, raw: i32) -> i32 {
+                raw
+            }
+        }
+
+        #[test]
+        fn given_aged_brie_at_i32_max_when_updated_then_saturates() {
+            // given
+            let item = Item::new("Aged Brie", 0, i32::MAX - 1);
+            let config = QualityConfig {
+                max_quality: i32::MAX,
+                ..QualityConfig::default()
+            };
+            let mut rose = GildedRose::with_config(vec![item], config);
+
+            // when
+            rose.advance_days(2);
+
+            // then
+            assert_eq!(rose.items[0].quality, i32::MAX);
+        }
+
+        #[test]
+        fn given_values_at_i32_min_when_updated_then_saturates() {
+            // given
+            let items = vec![
+                Item::new("Item", i32::MIN, i32::MIN),
+                Item::new("Conjured Mana Cake", i32::MIN, i32::MIN),
+            ];
+            let mut rose = GildedRose::with_policy(items, Unbounded);
+
+            // when
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0].sell_in, i32::MIN);
+            assert_eq!(rose.items[0].quality, i32::MIN);
+            assert_eq!(rose.items[1].quality, i32::MIN);
+        }
+
+        #[test]
+        fn given_huge_priced_stacks_when_valued_then_saturates() {
+            // given
+            let stack = Item {
+                quantity: u32::MAX,
+                ..Item::with_price("Gold", 5, i32::MAX, u32::MAX)
+            };
+            let rose = GildedRose::new(vec![stack.clone(), stack]);
+
+            // when
+            let value = rose.total_value();
+
+            // then
+            assert_eq!(value, u64::MAX);
+        }
+
+        #[test]
+        fn given_expired_backstage_pass_at_i32_min_when_updated_then_does_not_panic() {
+            // given
+            let item = Item::new("Backstage passes", 0, i32::MIN);
+            let mut rose = GildedRose::new(vec![item]);
+
+            // when
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0].quality, 0);
+        }
+    }
+
+    mod builder {
+        use crate::gildedrose::{Item, ItemBuilder};
+
+        #[test]
+        fn when_built_then_uses_named_fields() {
+            let item = ItemBuilder::new()
+                .quality(50)
+                .name("Item")
+                .sell_in(10)
+                .build();
+
+            assert_eq!(item, Item::new("Item", 10, 50));
+        }
+
+        #[test]
+        fn given_no_values_when_built_then_defaults_to_zero() {
+            let item = ItemBuilder::new().name("Item").build();
+
+            assert_eq!(item, Item::new("Item", 0, 0));
+        }
+
+        #[test]
+        fn given_sulfuras_without_quality_when_built_then_defaults_to_80() {
+            let item = ItemBuilder::new()
+                .name("Sulfuras, Hand of Ragnaros")
+                .build();
+
+            assert_eq!(item.quality, 80);
+        }
+
+        #[test]
+        fn given_sulfuras_with_quality_when_built_then_keeps_override() {
+            let item = ItemBuilder::new()
+                .name("Sulfuras, Hand of Ragnaros")
+                .quality(10)
+                .build();
+
+            assert_eq!(item.quality, 10);
+        }
+    }
+
+    mod predicates {
+        use crate::gildedrose::{Item, ItemKind};
+
+        #[test]
+        fn given_sulfuras_when_checked_then_is_legendary() {
+            assert!(Item::new("Sulfuras, Hand of Ragnaros", 0, 80).is_legendary());
+            assert!(!Item::new("My Sulfuras Replica", 0, 80).is_legendary());
+            assert!(!Item::new("Aged Brie", 0, 10).is_legendary());
+        }
+
+        #[test]
+        fn given_explicit_legendary_kind_when_checked_then_is_legendary() {
+            let mut item = Item::new("Custom Sword", 0, 80);
+            item.kind = Some(ItemKind::Sulfuras);
+
+            assert!(item.is_legendary());
+        }
+
+        #[test]
+        fn given_negative_sell_in_when_checked_then_is_expired() {
+            assert!(Item::new("Item", -1, 10).is_expired());
+            assert!(!Item::new("Item", 0, 10).is_expired());
+        }
+
+        #[test]
+        fn given_sulfuras_at_negative_sell_in_when_checked_then_is_not_expired() {
+            assert!(!Item::new("Sulfuras, Hand of Ragnaros", -1, 80).is_expired());
+        }
+    }
+
+    mod checked_update {
+        use crate::gildedrose::{GildedRose, Item, ItemError};
+
+        #[test]
+        fn given_valid_items_when_updated_checked_then_updates_normally() {
+            // given
+            let items = vec![
+                Item::new("Item", 10, 20),
+                Item::new("Sulfuras, Hand of Ragnaros", 0, 80),
+            ];
+            let mut rose = GildedRose::new(items);
+
+            // when
+            let result = rose.update_quality_checked();
+
+            // then
+            assert_eq!(result, Ok(()));
+            assert_eq!(rose.items[0], Item::new("Item", 9, 19));
+            assert_eq!(
+                rose.items[1],
+                Item::new("Sulfuras, Hand of Ragnaros", 0, 80)
+            );
+        }
+
+        #[test]
+        fn given_sulfuras_with_wrong_quality_when_updated_checked_then_reports_and_keeps_it() {
+            // given
+            let items = vec![
+                Item::new("Sulfuras, Hand of Ragnaros", 0, 10),
+                Item::new("Item", 10, 20),
+            ];
+            let mut rose = GildedRose::new(items);
+
+            // when
+            let result = rose.update_quality_checked();
+
+            // then
+            assert_eq!(
+                result,
+                Err(vec![ItemError::InvalidLegendaryQuality {
+                    name: "Sulfuras, Hand of Ragnaros".to_string(),
+                    quality: 10,
+                }])
+            );
+            assert_eq!(rose.items[0].quality, 10);
+            assert_eq!(rose.items[1].quality, 19);
+        }
+
+        #[test]
+        fn given_sulfuras_with_wrong_quality_when_updated_then_still_corrects_it() {
+            // given
+            let item = Item::new("Sulfuras, Hand of Ragnaros", 0, 10);
+            let mut rose = GildedRose::new(vec![item]);
+
+            // when
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0].quality, 80);
+        }
+    }
+
+    mod legendary {
+        use crate::gildedrose::{GildedRose, Item, ItemError};
+
+        fn rose(items: Vec<Item>) -> GildedRose {
+            let mut rose = GildedRose::new(items);
+            rose.register_legendary("Ashbringer", 100);
+            rose
+        }
+
+        #[test]
+        fn given_registered_legendary_when_updated_then_keeps_fixed_quality_and_sell_in() {
+            // given
+            let mut rose = rose(vec![Item::new("Ashbringer", 3, 100)]);
+
+            // when
+            rose.advance_days(5);
+
+            // then
+            assert_eq!(rose.items[0], Item::new("Ashbringer", 3, 100));
+        }
+
+        #[test]
+        fn given_registered_legendary_with_other_quality_when_updated_then_resets_to_fixed() {
+            // given
+            let mut rose = rose(vec![Item::new("Ashbringer", 3, 10)]);
+
+            // when
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0].quality, 100);
+        }
+
+        #[test]
+        fn given_registered_legendary_when_updated_then_sulfuras_is_still_80() {
+            // given
+            let mut rose = rose(vec![Item::new("Sulfuras, Hand of Ragnaros", 0, 80)]);
+
+            // when
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0].quality, 80);
+        }
+
+        #[test]
+        fn given_registered_legendary_when_updated_checked_then_validates_fixed_quality() {
+            // given
+            let mut rose = rose(vec![
+                Item::new("Ashbringer", 3, 100),
+                Item::new("Ashbringer", 3, 80),
+            ]);
+
+            // when
+            let result = rose.update_quality_checked();
+
+            // then
+            assert_eq!(
+                result,
+                Err(vec![ItemError::InvalidLegendaryQuality {
+                    name: "Ashbringer".to_string(),
+                    quality: 80,
+                }])
+            );
+        }
+
+        #[test]
+        fn given_expired_registered_legendary_when_counted_then_is_not_expired() {
+            // given
+            let rose = rose(vec![
+                Item::new("Ashbringer", -3, 100),
+                Item::new("Item", -1, 3),
+            ]);
+
+            // then
+            assert_eq!(rose.count_expired(), 1);
+        }
+    }
+
+    mod history {
+        use crate::gildedrose::{GildedRose, Item};
+
+        #[test]
+        fn given_history_when_advanced_three_days_and_undone_twice_then_restores_day_one() {
+            // given
+            let mut rose = GildedRose::new(vec![
+                Item::new("Item", 10, 20),
+                Item::new("Aged Brie", 2, 0),
+            ]);
+            rose.enable_history();
+            rose.update_quality();
+            let day_one = rose.items.clone();
+            rose.update_quality();
+            rose.update_quality();
+
+            // when
+            let undone = rose.undo() && rose.undo();
+
+            // then
+            assert!(undone);
+            assert_eq!(rose.items, day_one);
+            assert_eq!(rose.history_len(), 1);
+        }
+
+        #[test]
+        fn given_history_when_advanced_then_every_day_can_be_undone() {
+            // given
+            let items = vec![Item::new("Backstage passes", 3, 20)];
+            let mut rose = GildedRose::new(items.clone());
+            rose.enable_history();
+
+            // when
+            rose.advance_days(3);
+
+            // then
+            assert_eq!(rose.history_len(), 3);
+            while rose.undo() {}
+            assert_eq!(rose.items, items);
+        }
+
+        #[test]
+        fn given_empty_history_when_undone_then_returns_false() {
+            // given
+            let mut rose = GildedRose::new(vec![Item::new("Item", 10, 20)]);
+            rose.enable_history();
+
+            // then
+            assert!(!rose.undo());
+        }
+
+        #[test]
+        fn given_history_disabled_when_updated_then_nothing_is_recorded() {
+            // given
+            let mut rose = GildedRose::new(vec![Item::new("Item", 10, 20)]);
+
+            // when
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.history_len(), 0);
+            assert!(!rose.undo());
+            assert_eq!(rose.items[0].quality, 19);
+        }
+    }
+
+    mod inventory {
+        use crate::gildedrose::{GildedRose, Item};
+
+        #[test]
+        fn when_item_added_and_removed_then_round_trips() {
+            // given
+            let mut rose = GildedRose::new(vec![Item::new("Item", 10, 20)]);
+
+            // when
+            rose.add_item(Item::new("Aged Brie", 2, 0));
+
+            // then
+            assert_eq!(rose.len(), 2);
+            assert_eq!(
+                rose.remove_item("Aged Brie"),
+                Some(Item::new("Aged Brie", 2, 0))
+            );
+            assert_eq!(rose.items, vec![Item::new("Item", 10, 20)]);
+        }
+
+        #[test]
+        fn given_duplicate_names_when_removed_then_removes_first_match() {
+            // given
+            let mut rose = GildedRose::new(vec![Item::new("Item", 1, 1), Item::new("Item", 2, 2)]);
+
+            // when
+            let removed = rose.remove_item("Item");
+
+            // then
+            assert_eq!(removed, Some(Item::new("Item", 1, 1)));
+            assert_eq!(rose.items, vec![Item::new("Item", 2, 2)]);
+        }
+
+        #[test]
+        fn given_unknown_name_when_removed_then_returns_none() {
+            // given
+            let mut rose = GildedRose::new(vec![Item::new("Item", 1, 1)]);
+
+            // then
+            assert_eq!(rose.remove_item("Aged Brie"), None);
+            assert_eq!(rose.len(), 1);
+        }
+
+        #[test]
+        fn given_empty_shop_when_removed_then_returns_none() {
+            // given
+            let mut rose = GildedRose::new(vec![]);
+
+            // then
+            assert!(rose.is_empty());
+            assert_eq!(rose.remove_item("Item"), None);
+        }
+    }
+
+    mod group_by_kind {
+        use crate::gildedrose::{GildedRose, Item, ItemKind};
+
+        #[test]
+        fn when_grouped_then_buckets_items_by_kind_in_insertion_order() {
+            // given
+            let rose = GildedRose::new(vec![
+                Item::new("+5 Dexterity Vest", 10, 20),
+                Item::new("Backstage passes to a TAFKAL80ETC concert", 15, 20),
+                Item::new("Elixir of the Mongoose", 5, 7),
+                Item::new("Sulfuras, Hand of Ragnaros", 0, 80),
+                Item::new("Backstage passes to a Metallica concert", 5, 49),
+                Item::new("Item", 1, 1),
+            ]);
+
+            // when
+            let groups = rose.group_by_kind();
+
+            // then
+            let names = |kind| -> Vec<&str> {
+                groups[&kind]
+                    .iter()
+                    .map(|item| item.name.as_str())
+                    .collect()
+            };
+            assert_eq!(groups.len(), 3);
+            assert_eq!(
+                names(ItemKind::Normal),
+                vec!["+5 Dexterity Vest", "Elixir of the Mongoose", "Item"]
+            );
+            assert_eq!(
+                names(ItemKind::BackstagePasses),
+                vec![
+                    "Backstage passes to a TAFKAL80ETC concert",
+                    "Backstage passes to a Metallica concert"
+                ]
+            );
+            assert_eq!(
+                names(ItemKind::Sulfuras),
+                vec!["Sulfuras, Hand of Ragnaros"]
+            );
+            assert!(!groups.contains_key(&ItemKind::AgedBrie));
+        }
+
+        #[test]
+        fn given_empty_shop_when_grouped_then_is_empty() {
+            assert!(GildedRose::new(vec![]).group_by_kind().is_empty());
+        }
+    }
+
+    mod simulate {
+        use crate::gildedrose::{GildedRose, Item};
+
+        #[test]
+        fn given_backstage_pass_when_simulated_then_follows_quality_curve() {
+            // given
+            let item = Item::new("Backstage passes", 10, 20);
+            let rose = GildedRose::new(vec![item.clone()]);
+
+            // when
+            let snapshots = rose.simulate(15);
+
+            // then
+            let qualities: Vec<i32> = snapshots.iter().map(|items| items[0].quality).collect();
+            assert_eq!(
+                qualities,
+                vec![20, 22, 24, 26, 28, 30, 33, 36, 39, 42, 45, 0, 0, 0, 0, 0]
+            );
+            assert_eq!(rose.items, vec![item]);
+        }
+
+        #[test]
+        fn given_zero_days_when_simulated_then_returns_current_state() {
+            // given
+            let rose = GildedRose::new(vec![Item::new("Item", 1, 1)]);
+
+            // when
+            let snapshots = rose.simulate(0);
+
+            // then
+            assert_eq!(snapshots, vec![rose.items.clone()]);
+        }
+    }
+
+    mod day_counter {
+        use crate::gildedrose::{GildedRose, Item};
+
+        #[test]
+        fn given_fresh_shop_then_starts_at_day_zero() {
+            assert_eq!(GildedRose::new(vec![]).current_day(), 0);
+        }
+
+        #[test]
+        fn when_updated_then_counts_days() {
+            // given
+            let mut rose = GildedRose::new(vec![Item::new("Item", 10, 20)]);
+
+            // when
+            rose.update_quality();
+            rose.update_quality_with_report();
+            let _ = rose.update_quality_checked();
+
+            // then
+            assert_eq!(rose.current_day(), 3);
+        }
+
+        #[test]
+        fn when_advanced_then_adds_days() {
+            // given
+            let mut rose = GildedRose::new(vec![Item::new("Item", 10, 20)]);
+            rose.update_quality();
+
+            // when
+            rose.advance_days(6);
+
+            // then
+            assert_eq!(rose.current_day(), 7);
+        }
+
+        #[test]
+        fn given_history_when_undone_then_steps_day_back() {
+            // given
+            let mut rose = GildedRose::new(vec![Item::new("Item", 10, 20)]);
+            rose.enable_history();
+            rose.advance_days(2);
+
+            // when
+            rose.undo();
+
+            // then
+            assert_eq!(rose.current_day(), 1);
+        }
+
+        #[test]
+        fn when_updated_until_day_then_applies_missing_updates() {
+            // given
+            let mut rose = GildedRose::new(vec![Item::new("Item", 10, 20)]);
+
+            // when
+            rose.update_until(5);
+
+            // then
+            assert_eq!(rose.current_day(), 5);
+            assert_eq!(rose.items[0], Item::new("Item", 5, 15));
+        }
+
+        #[test]
+        fn given_later_day_when_updated_until_earlier_day_then_does_nothing() {
+            // given
+            let mut rose = GildedRose::new(vec![Item::new("Item", 10, 20)]);
+            rose.advance_days(3);
+
+            // when
+            rose.update_until(2);
+
+            // then
+            assert_eq!(rose.current_day(), 3);
+            assert_eq!(rose.items[0], Item::new("Item", 7, 17));
+        }
+    }
+
+    mod value {
+        use crate::gildedrose::{GildedRose, Item};
+
+        #[test]
+        fn given_priced_and_unpriced_items_when_valued_then_sums_priced_items_only() {
+            // given
+            let rose = GildedRose::new(vec![
+                Item::with_price("Aged Brie", 2, 10, 3),
+                Item::new("Item", 5, 40),
+                Item::with_price("Sulfuras, Hand of Ragnaros", 0, 80, 1000),
+            ]);
+
+            // then
+            assert_eq!(rose.total_value(), 30 + 80_000);
+        }
+
+        #[test]
+        fn given_new_item_then_has_no_price() {
+            assert_eq!(Item::new("Item", 5, 40).price, None);
+            assert_eq!(Item::with_price("Item", 5, 40, 7).price, Some(7));
+        }
+
+        #[test]
+        fn given_items_without_prices_when_valued_then_is_zero() {
+            assert_eq!(
+                GildedRose::new(vec![Item::new("Item", 5, 40)]).total_value(),
+                0
+            );
+        }
+    }
+
+    mod decay_profile {
+        use crate::gildedrose::{DecayProfile, GildedRose, Item, QualityConfig};
+
+        fn quality_after_update(profile: DecayProfile, sell_in: i32) -> i32 {
+            let config = QualityConfig {
+                decay_profile: profile,
+                ..QualityConfig::default()
+            };
+            let mut rose = GildedRose::with_config(vec![Item::new("Item", sell_in, 20)], config);
+            rose.update_quality();
+            rose.items[0].quality
+        }
+
+        #[test]
+        fn given_linear_profile_when_updated_then_loses_one_even_after_expiry() {
+            assert_eq!(quality_after_update(DecayProfile::Linear, 5), 19);
+            assert_eq!(quality_after_update(DecayProfile::Linear, -1), 19);
+        }
+
+        #[test]
+        fn given_double_profile_when_updated_then_loses_two_after_expiry() {
+            assert_eq!(quality_after_update(DecayProfile::DoubleAfterExpiry, 5), 19);
+            assert_eq!(
+                quality_after_update(DecayProfile::DoubleAfterExpiry, -1),
+                18
+            );
+        }
+
+        #[test]
+        fn given_triple_profile_when_updated_then_loses_three_after_expiry() {
+            assert_eq!(quality_after_update(DecayProfile::TripleAfterExpiry, 5), 19);
+            assert_eq!(
+                quality_after_update(DecayProfile::TripleAfterExpiry, -1),
+                17
+            );
+        }
+
+        #[test]
+        fn given_custom_profile_when_updated_then_uses_custom_increment() {
+            fn steep(sell_in: i32) -> i32 {
+                if sell_in < 1 {
+                    -5
+                } else {
+                    -2
+                }
+            }
+
+            assert_eq!(quality_after_update(DecayProfile::Custom(steep), 5), 18);
+            assert_eq!(quality_after_update(DecayProfile::Custom(steep), -1), 15);
+        }
+
+        #[test]
+        fn given_default_config_then_profile_is_double_after_expiry() {
+            assert!(matches!(
+                QualityConfig::default().decay_profile,
+                DecayProfile::DoubleAfterExpiry
+            ));
+        }
+    }
+
+    mod collect {
+        use crate::gildedrose::{GildedRose, Item};
+
+        #[test]
+        fn when_collected_then_creates_shop_with_items() {
+            // given
+            let items = vec![Item::new("Item", 1, 2), Item::new("Aged Brie", 3, 4)];
+
+            // when
+            let rose: GildedRose = items.clone().into_iter().collect();
+
+            // then
+            assert_eq!(rose.items, items);
+        }
+
+        #[test]
+        fn given_default_shop_then_is_empty() {
+            assert!(GildedRose::default().is_empty());
+        }
+
+        #[test]
+        fn given_default_shop_when_extended_then_contains_items() {
+            // given
+            let mut rose = GildedRose::default();
+
+            // when
+            rose.extend(vec![Item::new("Item", 1, 2)]);
+
+            // then
+            assert_eq!(rose.len(), 1);
+        }
+
+        #[test]
+        fn when_extended_then_appends_items() {
+            // given
+            let mut rose = GildedRose::new(vec![Item::new("Item", 1, 2)]);
+
+            // when
+            rose.extend(vec![Item::new("Aged Brie", 3, 4)]);
+
+            // then
+            assert_eq!(
+                rose.items,
+                vec![Item::new("Item", 1, 2), Item::new("Aged Brie", 3, 4)]
+            );
+        }
+    }
+
+    mod retain {
+        use crate::gildedrose::{GildedRose, Item};
+
+        #[test]
+        fn when_retained_then_keeps_matching_items() {
+            // given
+            let mut rose =
+                GildedRose::new(vec![Item::new("Item", 1, 2), Item::new("Aged Brie", 3, 4)]);
+
+            // when
+            rose.retain(|item| item.quality > 2);
+
+            // then
+            assert_eq!(rose.items, vec![Item::new("Aged Brie", 3, 4)]);
+        }
+
+        #[test]
+        fn when_worthless_discarded_then_removes_normal_items_at_zero_and_keeps_sulfuras() {
+            // given
+            let mut rose = GildedRose::new(vec![
+                Item::new("Item", -3, 0),
+                Item::new("Sulfuras, Hand of Ragnaros", 0, 80),
+                Item::new("Aged Brie", 3, 1),
+                Item::new("Backstage passes", -1, 0),
+            ]);
+
+            // when
+            rose.discard_worthless();
+
+            // then
+            assert_eq!(
+                rose.items,
+                vec![
+                    Item::new("Sulfuras, Hand of Ragnaros", 0, 80),
+                    Item::new("Aged Brie", 3, 1),
+                ]
+            );
+        }
+
+        #[test]
+        fn given_legendary_with_zero_quality_when_worthless_discarded_then_keeps_it() {
+            // given
+            let mut rose = GildedRose::new(vec![Item::new("Sulfuras, Hand of Ragnaros", 0, 0)]);
+
+            // when
+            rose.discard_worthless();
+
+            // then
+            assert_eq!(rose.len(), 1);
+        }
+    }
+
+    mod next_item_state {
+        use crate::gildedrose::{next_item_state, GildedRose, Item};
+
+        fn assert_matches_update(item: Item) {
+            let next = next_item_state(&item);
+            let mut rose = GildedRose::new(vec![item]);
+            rose.update_quality();
+            assert_eq!(next, rose.items[0]);
+        }
+
+        #[test]
+        fn given_normal_item_then_matches_update() {
+            assert_matches_update(Item::new("Item", 0, 10));
+        }
+
+        #[test]
+        fn given_aged_brie_then_matches_update() {
+            assert_matches_update(Item::new("Aged Brie", 1, 49));
+        }
+
+        #[test]
+        fn given_sulfuras_then_matches_update() {
+            assert_matches_update(Item::new("Sulfuras, Hand of Ragnaros", -1, 80));
+        }
+
+        #[test]
+        fn given_backstage_pass_then_matches_update() {
+            assert_matches_update(Item::new("Backstage passes", 6, 30));
+            assert_matches_update(Item::new("Backstage passes", 0, 30));
+        }
+
+        #[test]
+        fn given_conjured_item_then_matches_update() {
+            assert_matches_update(Item::new("Conjured Mana Cake", 0, 3));
+        }
+
+        #[test]
+        fn when_computed_then_leaves_item_untouched() {
+            // given
+            let item = Item::new("Item", 5, 10);
+
+            // when
+            let next = next_item_state(&item);
+
+            // then
+            assert_eq!(next, Item::new("Item", 4, 9));
+            assert_eq!(item, Item::new("Item", 5, 10));
+        }
+    }
+
+    mod frozen {
+        use crate::gildedrose::{GildedRose, Item};
+
+        #[test]
+        fn given_frozen_item_when_updated_then_is_unchanged() {
+            // given
+            let mut item = Item::new("Item", 5, 10);
+            item.freeze();
+            let mut rose = GildedRose::new(vec![item]);
+
+            // when
+            rose.advance_days(3);
+
+            // then
+            assert_eq!(rose.items[0].sell_in, 5);
+            assert_eq!(rose.items[0].quality, 10);
+        }
+
+        #[test]
+        fn given_unfrozen_item_when_updated_then_decays_again() {
+            // given
+            let mut item = Item::new("Item", 5, 10);
+            item.freeze();
+            let mut rose = GildedRose::new(vec![item]);
+            rose.advance_days(3);
+
+            // when
+            rose.items[0].unfreeze();
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0].sell_in, 4);
+            assert_eq!(rose.items[0].quality, 9);
+        }
+
+        #[test]
+        fn given_frozen_legendary_with_wrong_quality_when_checked_update_then_is_skipped() {
+            // given
+            let mut item = Item::new("Sulfuras, Hand of Ragnaros", 0, 10);
+            item.freeze();
+            let mut rose = GildedRose::new(vec![item]);
+
+            // when
+            let result = rose.update_quality_checked();
+
+            // then
+            assert!(result.is_ok());
+            assert_eq!(rose.items[0].quality, 10);
+        }
+    }
+
+    mod quality_histogram {
+        use crate::gildedrose::{GildedRose, Item};
+        use std::collections::BTreeMap;
+
+        #[test]
+        fn given_spread_of_qualities_when_bucketed_by_ten_then_counts_per_bucket() {
+            // given
+            let rose = GildedRose::new(vec![
+                Item::new("Item", 5, 0),
+                Item::new("Item", 5, 9),
+                Item::new("Item", 5, 10),
+                Item::new("Item", 5, 25),
+                Item::new("Item", 5, 29),
+                Item::new("Sulfuras, Hand of Ragnaros", 0, 80),
+            ]);
+
+            // when
+            let histogram = rose.quality_histogram(10);
+
+            // then
+            let expected: BTreeMap<i32, usize> = vec![(0, 2), (10, 1), (20, 2), (80, 1)]
+                .into_iter()
+                .collect();
+            assert_eq!(histogram, expected);
+        }
+
+        #[test]
+        fn given_negative_quality_when_bucketed_then_rounds_down() {
+            let rose = GildedRose::new(vec![Item::new("Item", 5, -3)]);
+
+            assert_eq!(rose.quality_histogram(10).get(&-10), Some(&1));
+        }
+
+        #[test]
+        fn given_minimum_quality_when_bucketed_then_lowest_bucket_saturates() {
+            // given
+            let rose = GildedRose::new(vec![
+                Item::new("Item", 5, i32::MIN),
+                Item::new("Item", 5, i32::MAX),
+            ]);
+
+            // when
+            let histogram = rose.quality_histogram(10);
+
+            // then
+            let expected: BTreeMap<i32, usize> = vec![(i32::MIN, 1), (2_147_483_640, 1)]
+                .into_iter()
+                .collect();
+            assert_eq!(histogram, expected);
+        }
+
+        #[test]
+        #[should_panic(expected = "bucket_size must be positive")]
+        fn given_bucket_size_of_zero_when_bucketed_then_panics() {
+            GildedRose::new(vec![]).quality_histogram(0);
+        }
+    }
+
+    mod update_callback {
+        use crate::gildedrose::{GildedRose, Item};
+
+        fn items() -> Vec<Item> {
+            vec![
+                Item::new("Item", 10, 20),
+                Item::new("Aged Brie", 0, 10),
+                Item::new("Backstage passes", 0, 42),
+                Item::new("Sulfuras, Hand of Ragnaros", 0, 80),
+            ]
+        }
+
+        #[test]
+        fn when_updated_with_callback_then_deltas_match_report() {
+            // given
+            let mut rose = GildedRose::new(items());
+            let mut deltas = Vec::new();
+
+            // when
+            rose.update_quality_with(|before, after| {
+                deltas.push((
+                    after.quality - before.quality,
+                    after.sell_in - before.sell_in,
+                ))
+            });
+
+            // then
+            let report: Vec<_> = GildedRose::new(items())
+                .update_quality_with_report()
+                .into_iter()
+                .map(|change| (change.quality_delta, change.sell_in_delta))
+                .collect();
+            assert_eq!(deltas, report);
+        }
+
+        #[test]
+        fn when_updated_with_callback_then_applies_update() {
+            // given
+            let mut rose = GildedRose::new(items());
+            let mut expected = GildedRose::new(items());
+
+            // when
+            rose.update_quality_with(|_, _| {});
+            expected.update_quality();
+
+            // then
+            assert_eq!(rose.items, expected.items);
+            assert_eq!(rose.current_day(), 1);
+        }
+    }
+
+    mod find_by_name {
+        use crate::gildedrose::{GildedRose, Item};
+
+        fn rose() -> GildedRose {
+            GildedRose::new(vec![
+                Item::new("Aged Brie", 2, 10),
+                Item::new("Item", 5, 7),
+                Item::new("Item", 3, 4),
+            ])
+        }
+
+        #[test]
+        fn given_present_name_when_found_then_returns_item() {
+            assert_eq!(
+                rose().find_by_name("Aged Brie"),
+                Some(&Item::new("Aged Brie", 2, 10))
+            );
+        }
+
+        #[test]
+        fn given_absent_name_when_found_then_returns_none() {
+            assert_eq!(rose().find_by_name("Elixir"), None);
+        }
+
+        #[test]
+        fn given_duplicate_names_when_found_then_returns_first() {
+            assert_eq!(rose().find_by_name("Item"), Some(&Item::new("Item", 5, 7)));
+        }
+
+        #[test]
+        fn when_found_mutably_then_edits_first_match() {
+            // given
+            let mut rose = rose();
+
+            // when
+            if let Some(item) = rose.find_by_name_mut("Item") {
+                item.quality = 30;
+            }
+
+            // then
+            assert_eq!(rose.items[1].quality, 30);
+            assert_eq!(rose.items[2].quality, 4);
+        }
+    }
+
+    mod decay_multiplier {
+        use crate::gildedrose::{GildedRose, Item};
+
+        fn updated(name: &str, sell_in: i32, quality: i32, decay_multiplier: i32) -> Item {
+            let item = Item {
+                decay_multiplier,
+                ..Item::new(name, sell_in, quality)
+            };
+            let mut rose = GildedRose::new(vec![item]);
+            rose.update_quality();
+            rose.items.remove(0)
+        }
+
+        #[test]
+        fn given_multiplier_of_zero_when_updated_then_never_decays() {
+            assert_eq!(updated("Item", 5, 20, 0).quality, 20);
+            assert_eq!(updated("Item", 0, 20, 0).quality, 20);
+        }
+
+        #[test]
+        fn given_multiplier_of_one_when_updated_then_decays_normally() {
+            assert_eq!(updated("Item", 5, 20, 1).quality, 19);
+            assert_eq!(updated("Conjured Mana Cake", 5, 20, 1).quality, 18);
+        }
+
+        #[test]
+        fn given_multiplier_of_three_when_updated_then_decays_three_times_as_fast() {
+            assert_eq!(updated("Item", 5, 20, 3).quality, 17);
+            assert_eq!(updated("Item", 0, 20, 3).quality, 14);
+            assert_eq!(updated("Conjured Mana Cake", 5, 20, 3).quality, 14);
+        }
+
+        #[test]
+        fn given_multiplier_of_two_when_updated_then_normal_item_decays_like_conjured() {
+            assert_eq!(
+                updated("Item", 0, 20, 2).quality,
+                updated("Conjured Mana Cake", 0, 20, 1).quality
+            );
+        }
+
+        #[test]
+        fn given_legendary_with_multiplier_when_updated_then_ignores_it() {
+            assert_eq!(updated("Sulfuras, Hand of Ragnaros", 0, 80, 3).quality, 80);
+        }
+    }
+
+    mod updated {
+        use crate::gildedrose::{BackstageConfig, GildedRose, Item, QualityConfig};
+
+        #[test]
+        fn when_updated_then_returns_advanced_shop_and_leaves_original() {
+            // given
+            let items = vec![Item::new("Item", 5, 10), Item::new("Aged Brie", 2, 10)];
+            let rose = GildedRose::new(items.clone());
+
+            // when
+            let updated = rose.updated();
+
+            // then
+            assert_eq!(rose.items, items);
+            assert_eq!(rose.current_day(), 0);
+            assert_eq!(
+                updated.items,
+                vec![Item::new("Item", 4, 9), Item::new("Aged Brie", 1, 11)]
+            );
+            assert_eq!(updated.current_day(), 1);
+        }
+
+        #[test]
+        fn given_custom_config_when_updated_then_keeps_rules() {
+            // given
+            let config = QualityConfig {
+                backstage: BackstageConfig {
+                    tier2_days: 20,
+                    tier3_days: 5,
+                },
+                ..QualityConfig::default()
+            };
+            let rose = GildedRose::with_config(vec![Item::new("Backstage passes", 15, 10)], config);
+
+            // when
+            let updated = rose.updated().updated();
+
+            // then
+            assert_eq!(updated.items[0].quality, 14);
+        }
+    }
+
+    mod checkpoint {
+        use crate::gildedrose::{GildedRose, Item};
+
+        #[test]
+        fn given_checkpoint_when_restored_after_updates_then_items_match_checkpoint() {
+            // given
+            let mut rose = GildedRose::new(vec![
+                Item::new("Item", 5, 10),
+                Item::new("Aged Brie", 2, 10),
+                Item::new("Backstage passes", 3, 20),
+            ]);
+            let checkpoint = rose.checkpoint();
+            rose.advance_days(5);
+
+            // when
+            rose.restore(checkpoint.clone());
+
+            // then
+            assert_eq!(rose.items, checkpoint);
+        }
+    }
+
+    mod backstage_boundaries {
+        use crate::gildedrose::{GildedRose, Item};
+
+        #[test]
+        fn given_each_boundary_sell_in_when_updated_then_uses_its_tier() {
+            let expected_qualities = [
+                (12, 21),
+                (11, 21),
+                (10, 22),
+                (9, 22),
+                (8, 22),
+                (7, 22),
+                (6, 22),
+                (5, 23),
+                (4, 23),
+                (3, 23),
+                (2, 23),
+                (1, 23),
+                (0, 0),
+                (-1, 0),
+            ];
+            for &(sell_in, expected_quality) in expected_qualities.iter() {
+                // given
+                let mut rose = GildedRose::new(vec![Item::new("Backstage passes", sell_in, 20)]);
+
+                // when
+                rose.update_quality();
+
+                // then
+                assert_eq!(
+                    rose.items[0].quality, expected_quality,
+                    "sell_in {}",
+                    sell_in
+                );
+            }
+        }
+    }
+
+    mod ripening {
+        use crate::gildedrose::{GildedRose, Item};
+
+        #[test]
+        fn given_days_before_peak_when_updated_then_increases_by_one() {
+            // given
+            let mut rose = GildedRose::new(vec![Item::new("Cheese wheel", 6, 20)]);
+
+            // when
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0], Item::new("Cheese wheel", 5, 21));
+        }
+
+        #[test]
+        fn when_updated_across_peak_then_ripens_then_spoils() {
+            // given
+            let rose = GildedRose::new(vec![Item::new("Cheese wheel", 2, 20)]);
+
+            // when
+            let snapshots = rose.simulate(5);
+
+            // then
+            let qualities: Vec<i32> = snapshots.iter().map(|items| items[0].quality).collect();
+            assert_eq!(qualities, vec![20, 21, 22, 20, 18, 16]);
+        }
+
+        #[test]
+        fn given_quality_near_bounds_when_updated_then_is_clamped() {
+            // given
+            let mut rose = GildedRose::new(vec![
+                Item::new("Cheese wheel", 5, 50),
+                Item::new("Cheese wheel", -1, 1),
+            ]);
+
+            // when
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0].quality, 50);
+            assert_eq!(rose.items[1].quality, 0);
+        }
+    }
+
+    mod static_dispatch {
+        use crate::gildedrose::{Calculations, CalculatorFactory, Item, ItemState, Rules};
+
+        #[test]
+        fn given_every_kind_when_updated_through_enum_then_matches_boxed_calculator() {
+            let rules = Rules::default();
+            let factory = CalculatorFactory::default();
+            let items = vec![
+                Item::new("Item", 5, 20),
+                Item::new("Item", 0, 20),
+                Item::new("Aged Brie", 0, 49),
+                Item::new("Backstage passes", 11, 20),
+                Item::new("Backstage passes", 5, 20),
+                Item::new("Backstage passes", 0, 20),
+                Item::new("Sulfuras, Hand of Ragnaros", -1, 80),
+                Item::new("Conjured Mana Cake", 0, 20),
+                Item::new("Cheese wheel", 1, 20),
+                Item::new("Cheese wheel", 0, 20),
+            ];
+            for item in &items {
+                // given
+                let state = ItemState::of(item);
+
+                // when
+                let by_enum = state.next(&factory.calculator_kind(item), &rules);
+                let boxed = state.next(factory.create_calculator(item).as_ref(), &rules);
+
+                // then
+                assert_eq!(
+                    (by_enum.sell_in, by_enum.quality),
+                    (boxed.sell_in, boxed.quality),
+                    "{}",
+                    item
+                );
+                assert_eq!(
+                    factory.calculator_kind(item).fixed_quality(),
+                    factory.create_calculator(item).fixed_quality()
+                );
+            }
+        }
+    }
+
+    mod quality_delta {
+        use crate::gildedrose::{
+            CalculateQuality, CalculateSellIn, Calculations, GildedRose, Item, QualityConfig,
+        };
+
+        fn rose() -> GildedRose {
+            GildedRose::new(vec![
+                Item::new("Item", 5, 25),
+                Item::new("Elixir", 5, 4),
+                Item::new("Sulfuras, Hand of Ragnaros", 0, 80),
+            ])
+        }
+
+        #[test]
+        fn given_markdown_when_applied_then_clamps_at_zero() {
+            // given
+            let mut rose = rose();
+
+            // when
+            rose.apply_quality_delta(-10);
+
+            // then
+            let qualities: Vec<i32> = rose.iter().map(|item| item.quality).collect();
+            assert_eq!(qualities, vec![15, 0, 80]);
+        }
+
+        #[test]
+        fn given_large_markup_when_applied_then_clamps_at_50() {
+            // given
+            let mut rose = rose();
+
+            // when
+            rose.apply_quality_delta(100);
+
+            // then
+            let qualities: Vec<i32> = rose.iter().map(|item| item.quality).collect();
+            assert_eq!(qualities, vec![50, 50, 80]);
+        }
+
+        #[test]
+        fn given_kind_with_own_bounds_when_marked_up_then_uses_them() {
+            // given
+            struct Promo;
+            impl CalculateQuality for Promo {
+                fn calculate_new_quality(&self, _: i32, quality: i32, _: &QualityConfig) -> i32 {
+                    quality
+                }
+            }
+            impl CalculateSellIn for Promo {}
+            impl Calculations for Promo {
+                fn quality_bounds(&self, _: &QualityConfig) -> Option<(i32, i32)> {
+                    Some((0, 60))
+                }
+            }
+            let mut rose = GildedRose::new(vec![
+                Item::new("Promo Vest", 5, 55),
+                Item::new("Promo Hat", 5, 59),
+            ]);
+            rose.register_calculator(|name| name.starts_with("Promo"), || Box::new(Promo));
+
+            // when
+            rose.apply_quality_delta(2);
+
+            // then
+            let qualities: Vec<i32> = rose.iter().map(|item| item.quality).collect();
+            assert_eq!(qualities, vec![57, 60]);
+        }
+    }
+
+    mod sorting {
+        use crate::gildedrose::{GildedRose, Item};
+
+        fn names(rose: &GildedRose) -> Vec<&str> {
+            rose.iter().map(|item| item.name.as_str()).collect()
+        }
+
+        #[test]
+        fn when_sorted_by_quality_then_descends_and_keeps_ties_in_order() {
+            // given
+            let mut rose = GildedRose::new(vec![
+                Item::new("Low", 5, 5),
+                Item::new("First tie", 3, 20),
+                Item::new("High", 1, 40),
+                Item::new("Second tie", 9, 20),
+            ]);
+
+            // when
+            rose.sort_by_quality();
+
+            // then
+            assert_eq!(names(&rose), vec!["High", "First tie", "Second tie", "Low"]);
+        }
+
+        #[test]
+        fn when_sorted_by_sell_in_then_ascends_and_keeps_ties_in_order() {
+            // given
+            let mut rose = GildedRose::new(vec![
+                Item::new("Late", 9, 5),
+                Item::new("First tie", 3, 20),
+                Item::new("Expired", -1, 40),
+                Item::new("Second tie", 3, 10),
+            ]);
+
+            // when
+            rose.sort_by_sell_in();
+
+            // then
+            assert_eq!(
+                names(&rose),
+                vec!["Expired", "First tie", "Second tie", "Late"]
+            );
+        }
+    }
+
+    mod map_items {
+        use crate::gildedrose::{GildedRose, Item};
+
+        #[test]
+        fn when_mapped_then_returns_transformed_shop_and_leaves_original() {
+            // given
+            let items = vec![Item::new("Aged Brie", 2, 10), Item::new("Elixir", 5, 7)];
+            let rose = GildedRose::new(items.clone());
+
+            // when
+            let mapped = rose.map_items(|item| Item {
+                name: item.name.to_uppercase(),
+                ..item.clone()
+            });
+
+            // then
+            assert_eq!(rose.items, items);
+            assert_eq!(
+                mapped.items,
+                vec![Item::new("AGED BRIE", 2, 10), Item::new("ELIXIR", 5, 7)]
+            );
+        }
+    }
+
+    mod time_step {
+        use crate::gildedrose::{GildedRose, Item, QualityConfig, TimeStep};
+
+        fn rose_with_step(items: Vec<Item>, days: u32) -> GildedRose {
+            let config = QualityConfig {
+                time_step: TimeStep { days },
+                ..QualityConfig::default()
+            };
+            GildedRose::with_config(items, config)
+        }
+
+        #[test]
+        fn given_step_of_one_when_updated_then_advances_one_day() {
+            // given
+            let mut rose = rose_with_step(vec![Item::new("Item", 5, 10)], 1);
+
+            // when
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0], Item::new("Item", 4, 9));
+        }
+
+        #[test]
+        fn given_step_of_three_when_updated_then_advances_three_days() {
+            // given
+            let mut rose = rose_with_step(
+                vec![
+                    Item::new("Item", 1, 10),
+                    Item::new("Backstage passes", 6, 10),
+                    Item::new("Sulfuras, Hand of Ragnaros", 0, 80),
+                ],
+                3,
+            );
+
+            // when
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0], Item::new("Item", -2, 5));
+            assert_eq!(rose.items[1], Item::new("Backstage passes", 3, 18));
+            assert_eq!(
+                rose.items[2],
+                Item::new("Sulfuras, Hand of Ragnaros", 0, 80)
+            );
+            assert_eq!(rose.current_day(), 3);
+        }
+
+        #[test]
+        fn given_step_of_three_when_updated_until_day_then_day_matches_elapsed_days() {
+            // given
+            let mut rose = rose_with_step(vec![Item::new("Item", 10, 20)], 3);
+
+            // when
+            rose.update_until(3);
+
+            // then
+            assert_eq!(rose.current_day(), 3);
+            assert_eq!(rose.items[0], Item::new("Item", 7, 17));
+
+            // when
+            rose.update_until(7);
+
+            // then
+            assert_eq!(rose.current_day(), 9);
+            assert_eq!(rose.items[0], Item::new("Item", 1, 11));
+        }
+
+        #[test]
+        fn given_step_of_three_when_undone_then_steps_back_three_days() {
+            // given
+            let mut rose = rose_with_step(vec![Item::new("Item", 10, 20)], 3);
+            rose.enable_history();
+            rose.advance_days(2);
+
+            // when
+            rose.undo();
+
+            // then
+            assert_eq!(rose.current_day(), 3);
+            assert_eq!(rose.items[0], Item::new("Item", 7, 17));
+        }
+
+        #[test]
+        fn given_step_of_zero_when_updated_until_day_then_does_nothing() {
+            let mut rose = rose_with_step(vec![Item::new("Item", 10, 20)], 0);
+
+            rose.update_until(5);
+
+            assert_eq!(rose.current_day(), 0);
+        }
+    }
+
+    mod expiring {
+        use crate::gildedrose::{GildedRose, Item, QualityConfig, TimeStep};
+
+        #[test]
+        fn when_listed_then_returns_only_items_at_sell_in_zero() {
+            // given
+            let rose = GildedRose::new(vec![
+                Item::new("Today", 0, 10),
+                Item::new("Tomorrow", 1, 10),
+                Item::new("Yesterday", -1, 10),
+                Item::new("Sulfuras, Hand of Ragnaros", 0, 80),
+                Item::new("Aged Brie", 0, 10),
+            ]);
+
+            // when
+            let expiring = rose.expiring_next_update();
+
+            // then
+            let names: Vec<&str> = expiring.iter().map(|item| item.name.as_str()).collect();
+            assert_eq!(names, vec!["Today", "Aged Brie"]);
+        }
+
+        #[test]
+        fn given_frozen_and_archived_items_when_listed_then_leaves_them_out() {
+            // given
+            let mut frozen = Item::new("Frozen", 0, 10);
+            frozen.freeze();
+            let mut rose = GildedRose::new(vec![
+                frozen,
+                Item::new("Archived", 0, 10),
+                Item::new("Today", 0, 10),
+            ]);
+            rose.archive("Archived");
+
+            // when
+            let expiring = rose.expiring_next_update();
+
+            // then
+            assert_eq!(expiring, vec![&Item::new("Today", 0, 10)]);
+        }
+
+        #[test]
+        fn given_step_of_three_days_when_listed_then_includes_items_expiring_within_it() {
+            // given
+            let config = QualityConfig {
+                time_step: TimeStep { days: 3 },
+                ..QualityConfig::default()
+            };
+            let rose = GildedRose::with_config(
+                vec![
+                    Item::new("In two days", 2, 10),
+                    Item::new("In three days", 3, 10),
+                ],
+                config,
+            );
+
+            // when
+            let expiring = rose.expiring_next_update();
+
+            // then
+            assert_eq!(expiring, vec![&Item::new("In two days", 2, 10)]);
+        }
+    }
+
+    mod partition_legendary {
+        use crate::gildedrose::{GildedRose, Item};
+
+        #[test]
+        fn when_partitioned_then_separates_legendary_items() {
+            // given
+            let mut rose = GildedRose::new(vec![
+                Item::new("Aged Brie", 2, 10),
+                Item::new("Sulfuras, Hand of Ragnaros", 0, 80),
+                Item::new("Backstage passes", 5, 20),
+                Item::new("Ashbringer", 0, 100),
+                Item::new("Item", 5, 7),
+            ]);
+            rose.register_legendary("Ashbringer", 100);
+
+            // when
+            let (legendary, others) = rose.partition_legendary();
+
+            // then
+            assert_eq!(
+                legendary,
+                vec![
+                    Item::new("Sulfuras, Hand of Ragnaros", 0, 80),
+                    Item::new("Ashbringer", 0, 100)
+                ]
+            );
+            assert_eq!(
+                others,
+                vec![
+                    Item::new("Aged Brie", 2, 10),
+                    Item::new("Backstage passes", 5, 20),
+                    Item::new("Item", 5, 7)
+                ]
+            );
+        }
+    }
+
+    mod conjured_config {
+        use crate::gildedrose::{ConjuredConfig, GildedRose, Item, QualityConfig};
+
+        fn rose_with_multiplier(item: Item, multiplier: i32) -> GildedRose {
+            let config = QualityConfig {
+                conjured: ConjuredConfig { multiplier },
+                ..QualityConfig::default()
+            };
+            GildedRose::with_config(vec![item], config)
+        }
+
+        #[test]
+        fn given_multiplier_of_3_when_updated_before_sell_date_then_decreases_by_three() {
+            // given
+            let mut rose = rose_with_multiplier(Item::new("Conjured Mana Cake", 5, 20), 3);
+
+            // when
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0].quality, 17);
+        }
+
+        #[test]
+        fn given_multiplier_of_3_when_updated_after_sell_date_then_decreases_by_six() {
+            // given
+            let mut rose = rose_with_multiplier(Item::new("Conjured Mana Cake", -1, 20), 3);
+
+            // when
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0].quality, 14);
+        }
+
+        #[test]
+        fn given_multiplier_of_3_when_updated_near_zero_then_clamps_at_zero() {
+            // given
+            let mut rose = rose_with_multiplier(Item::new("Conjured Mana Cake", -1, 4), 3);
+
+            // when
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0].quality, 0);
+        }
+    }
+
+    mod days_until_worthless {
+        use crate::gildedrose::{days_until_worthless, next_item_state, Item};
+
+        #[test]
+        fn given_normal_item_then_counts_accelerated_decay_after_expiry() {
+            // 10 -> 9 -> 8 -> 7, then expired: 5 -> 3 -> 1 -> 0
+            assert_eq!(days_until_worthless(&Item::new("Item", 3, 10)), Some(7));
+        }
+
+        #[test]
+        fn given_worthless_item_then_is_zero() {
+            assert_eq!(days_until_worthless(&Item::new("Item", 3, 0)), Some(0));
+        }
+
+        #[test]
+        fn given_backstage_pass_then_counts_until_concert() {
+            assert_eq!(
+                days_until_worthless(&Item::new("Backstage passes", 2, 10)),
+                Some(3)
+            );
+        }
+
+        #[test]
+        fn given_aged_brie_then_is_none() {
+            assert_eq!(days_until_worthless(&Item::new("Aged Brie", 5, 10)), None);
+        }
+
+        #[test]
+        fn given_sulfuras_then_is_none() {
+            assert_eq!(
+                days_until_worthless(&Item::new("Sulfuras, Hand of Ragnaros", 0, 80)),
+                None
+            );
+        }
+
+        #[test]
+        fn given_huge_sell_in_then_answers_without_stepping_every_day() {
+            let far = i32::MAX;
+
+            assert_eq!(days_until_worthless(&Item::new("Aged Brie", far, 10)), None);
+            assert_eq!(
+                days_until_worthless(&Item::new("Backstage passes", far, 10)),
+                Some(far as u32 + 1)
+            );
+            assert_eq!(
+                days_until_worthless(&Item::new("Cheese wheel", far, 10)),
+                Some(far as u32 + 25)
+            );
+            assert_eq!(
+                days_until_worthless(&Item::with_floor("Tea", far, 10, 1)),
+                None
+            );
+            let never_decays = Item {
+                decay_multiplier: 0,
+                ..Item::new("Item", far, 10)
+            };
+            assert_eq!(days_until_worthless(&never_decays), None);
+        }
+
+        #[test]
+        fn given_rising_kinds_then_matches_stepping_day_by_day() {
+            fn stepped(item: &Item) -> u32 {
+                let mut current = item.clone();
+                let mut days = 0;
+                while current.quality > 0 {
+                    current = next_item_state(&current);
+                    days += 1;
+                }
+                days
+            }
+            for name in ["Backstage passes", "Cheese wheel"].iter() {
+                for sell_in in -3..=15 {
+                    for quality in [1, 30, 49, 50, 60].iter() {
+                        let item = Item::new(*name, sell_in, *quality);
+                        assert_eq!(
+                            days_until_worthless(&item),
+                            Some(stepped(&item)),
+                            "{}",
+                            item
+                        );
+                    }
+                }
+            }
+        }
+
+        #[test]
+        fn given_frozen_item_then_is_none() {
+            // given
+            let mut item = Item::new("Item", 3, 10);
+
+            // when
+            item.freeze();
+
+            // then
+            assert_eq!(days_until_worthless(&item), None);
+        }
+    }
+
+    mod new_unique {
+        use crate::gildedrose::{DuplicateNameError, GildedRose, Item};
+
+        #[test]
+        fn given_unique_names_when_created_then_succeeds() {
+            // given
+            let items = vec![Item::new("Aged Brie", 2, 10), Item::new("Item", 5, 7)];
+
+            // when
+            let rose = GildedRose::new_unique(items.clone());
+
+            // then
+            assert_eq!(rose.map(|rose| rose.items), Ok(items));
+        }
+
+        #[test]
+        fn given_duplicate_name_when_created_then_reports_it() {
+            // given
+            let items = vec![
+                Item::new("Item", 5, 7),
+                Item::new("Aged Brie", 2, 10),
+                Item::new("Item", 3, 4),
+            ];
+
+            // when
+            let rose = GildedRose::new_unique(items);
+
+            // then
+            assert_eq!(
+                rose.err(),
+                Some(DuplicateNameError {
+                    name: "Item".to_string()
+                })
+            );
+        }
+    }
+
+    mod quality_bounds {
+        use crate::gildedrose::{
+            CalculateQuality, CalculateSellIn, Calculations, GildedRose, Item, QualityConfig,
+            QualityPolicy, StandardPolicy,
+        };
+
+        struct Promo;
+
+        impl CalculateQuality for Promo {
+            fn calculate_new_quality(&self, _: i32, quality: i32, _: &QualityConfig) -> i32 {
+                quality + 5
+            }
+        }
+
+        impl CalculateSellIn for Promo {}
+
+        impl Calculations for Promo {
+            fn quality_bounds(&self, _: &QualityConfig) -> Option<(i32, i32)> {
+                Some((0, 60))
+            }
+        }
+
+        fn rose(items: Vec<Item>) -> GildedRose {
+            let mut rose = GildedRose::new(items);
+            rose.register_calculator(|name| name.starts_with("Promo"), || Box::new(Promo));
+            rose
+        }
+
+        #[test]
+        fn given_promo_bounds_when_updated_then_climbs_past_50() {
+            // given
+            let mut rose = rose(vec![Item::new("Promo Vest", 5, 48)]);
+
+            // when
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0].quality, 53);
+        }
+
+        #[test]
+        fn given_promo_bounds_when_updated_then_caps_at_60() {
+            // given
+            let mut rose = rose(vec![
+                Item::new("Promo Vest", 5, 58),
+                Item::new("Item", 5, 50),
+            ]);
+
+            // when
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0].quality, 60);
+            assert_eq!(rose.items[1].quality, 49);
+        }
+
+        #[test]
+        fn given_standard_policy_with_own_range_when_updated_then_applies_it_beside_promo() {
+            // given
+            let policy = StandardPolicy {
+                min_quality: -10,
+                max_quality: 50,
+            };
+            let items = vec![Item::new("Item", 0, 1), Item::new("Promo Vest", 5, 58)];
+            let mut rose = GildedRose::with_policy(items, policy);
+            rose.register_calculator(|name| name.starts_with("Promo"), || Box::new(Promo));
+
+            // when
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0].quality, -1);
+            assert_eq!(rose.items[1].quality, 60);
+        }
+
+        #[test]
+        fn given_custom_policy_when_promo_updated_then_still_uses_promo_bounds() {
+            // given
+            struct Debt;
+            impl QualityPolicy for Debt {
+                fn clamp(&self, raw: i32) -> i32 {
+                    raw.clamp(-10, 50)
+                }
+            }
+            let mut rose = GildedRose::with_policy(vec![Item::new("Promo Vest", 5, 48)], Debt);
+            rose.register_calculator(|name| name.starts_with("Promo"), || Box::new(Promo));
+
+            // when
+            rose.advance_days(3);
+
+            // then
+            assert_eq!(rose.items[0].quality, 60);
+        }
+
+        #[test]
+        fn given_built_in_kinds_when_bounds_queried_then_only_legendary_has_own_bounds() {
+            // given
+            let rose = GildedRose::new(vec![]);
+            let config = QualityConfig::default();
+            let calculator = |item: Item| rose.rules.factory.create_calculator(&item);
+
+            // then
+            assert_eq!(
+                calculator(Item::new("Item", 5, 10)).quality_bounds(&config),
+                None
+            );
+            assert_eq!(
+                calculator(Item::new("Sulfuras, Hand of Ragnaros", 0, 80)).quality_bounds(&config),
+                Some((80, 80))
+            );
+        }
+    }
+
+    mod run {
+        use crate::gildedrose::{GildedRose, Item, ItemChange};
+
+        fn change(quality_delta: i32) -> Vec<ItemChange> {
+            vec![ItemChange {
+                name: "Backstage passes".to_string(),
+                quality_delta,
+                sell_in_delta: -1,
+                clamped: false,
+            }]
+        }
+
+        #[test]
+        fn given_backstage_pass_when_run_then_reports_each_day_including_crash() {
+            // given
+            let mut rose = GildedRose::new(vec![Item::new("Backstage passes", 2, 20)]);
+
+            // when
+            let changes = rose.run(3);
+
+            // then
+            assert_eq!(changes, vec![change(3), change(3), change(-26)]);
+            assert_eq!(rose.items[0], Item::new("Backstage passes", -1, 0));
+        }
+    }
+
+    mod setters {
+        use crate::gildedrose::{GildedRose, Item, ItemError, QualityConfig};
+
+        #[test]
+        fn given_normal_item_when_set_to_60_then_fails_and_keeps_quality() {
+            // given
+            let mut item = Item::new("Item", 5, 10);
+
+            // when
+            let result = item.set_quality(60);
+
+            // then
+            assert_eq!(
+                result,
+                Err(ItemError::QualityTooHigh {
+                    name: "Item".to_string(),
+                    quality: 60,
+                    max: 50,
+                })
+            );
+            assert_eq!(item.quality, 10);
+        }
+
+        #[test]
+        fn given_normal_item_when_set_to_30_then_updates_quality() {
+            // given
+            let mut item = Item::new("Item", 5, 10);
+
+            // when
+            let result = item.set_quality(30);
+
+            // then
+            assert_eq!(result, Ok(()));
+            assert_eq!(item.quality, 30);
+        }
+
+        #[test]
+        fn given_sulfuras_when_set_to_other_than_80_then_fails() {
+            let mut item = Item::new("Sulfuras, Hand of Ragnaros", 0, 80);
+
+            assert!(item.set_quality(50).is_err());
+        }
+
+        #[test]
+        fn given_registered_legendary_when_set_through_shop_then_uses_its_fixed_quality() {
+            // given
+            let mut rose = GildedRose::new(vec![Item::new("Excalibur", 0, 90)]);
+            rose.register_legendary("Excalibur", 100);
+
+            // when
+            let valid = rose.set_quality(0, 100);
+            let invalid = rose.set_quality(0, 80);
+
+            // then
+            assert_eq!(valid, Some(Ok(())));
+            assert_eq!(
+                invalid,
+                Some(Err(ItemError::InvalidLegendaryQuality {
+                    name: "Excalibur".to_string(),
+                    quality: 80,
+                }))
+            );
+            assert_eq!(rose.items[0].quality, 100);
+            assert!(Item::new("Excalibur", 0, 90).set_quality(100).is_err());
+        }
+
+        #[test]
+        fn given_configured_bounds_when_set_through_shop_then_uses_them() {
+            // given
+            let config = QualityConfig {
+                max_quality: 100,
+                ..QualityConfig::default()
+            };
+            let mut rose = GildedRose::with_config(vec![Item::new("Item", 5, 10)], config);
+
+            // when
+            let valid = rose.set_quality(0, 80);
+            let invalid = rose.set_quality(0, 101);
+
+            // then
+            assert_eq!(valid, Some(Ok(())));
+            assert!(matches!(invalid, Some(Err(_))));
+            assert_eq!(rose.items[0].quality, 80);
+        }
+
+        #[test]
+        fn given_missing_index_when_set_through_shop_then_is_none() {
+            let mut rose = GildedRose::new(vec![Item::new("Item", 5, 10)]);
+
+            assert_eq!(rose.set_quality(3, 10), None);
+            assert_eq!(rose.set_sell_in(3, 10), None);
+        }
+
+        #[test]
+        fn when_sell_in_set_then_updates_sell_in() {
+            // given
+            let mut item = Item::new("Item", 5, 10);
+
+            // when
+            let result = item.set_sell_in(-2);
+
+            // then
+            assert_eq!(result, Ok(()));
+            assert_eq!(item.sell_in, -2);
+        }
+
+        #[test]
+        fn given_sulfuras_when_sell_in_changed_then_fails_and_keeps_it() {
+            // given
+            let mut item = Item::new("Sulfuras, Hand of Ragnaros", 0, 80);
+
+            // when
+            let result = item.set_sell_in(5);
+
+            // then
+            assert_eq!(
+                result,
+                Err(ItemError::LegendarySellInChanged {
+                    name: "Sulfuras, Hand of Ragnaros".to_string(),
+                    sell_in: 5,
+                    expected: 0,
+                })
+            );
+            assert_eq!(item.sell_in, 0);
+        }
+
+        #[test]
+        fn given_min_sell_in_when_set_below_through_shop_then_fails() {
+            // given
+            let config = QualityConfig {
+                min_sell_in: Some(-5),
+                ..QualityConfig::default()
+            };
+            let mut rose = GildedRose::with_config(vec![Item::new("Item", 5, 10)], config);
+
+            // when
+            let valid = rose.set_sell_in(0, -5);
+            let invalid = rose.set_sell_in(0, -6);
+
+            // then
+            assert_eq!(valid, Some(Ok(())));
+            assert_eq!(
+                invalid,
+                Some(Err(ItemError::SellInTooLow {
+                    name: "Item".to_string(),
+                    sell_in: -6,
+                    min: -5,
+                }))
+            );
+            assert_eq!(rose.items[0].sell_in, -5);
+        }
+    }
+
+    mod sparkline {
+        use crate::gildedrose::{quality_sparkline, Item};
+
+        #[test]
+        fn when_rendered_then_has_one_char_per_day_including_today() {
+            assert_eq!(
+                quality_sparkline(&Item::new("Item", 5, 30), 10)
+                    .chars()
+                    .count(),
+                11
+            );
+        }
+
+        #[test]
+        fn given_sulfuras_when_rendered_then_is_flat() {
+            assert_eq!(
+                quality_sparkline(&Item::new("Sulfuras, Hand of Ragnaros", 0, 80), 4),
+                "█████"
+            );
+        }
+
+        #[test]
+        fn given_backstage_pass_when_rendered_then_rises_then_crashes() {
+            assert_eq!(
+                quality_sparkline(&Item::new("Backstage passes", 2, 40), 3),
+                "▆▇▇▁"
+            );
+        }
+    }
+
+    mod quantity {
+        use crate::gildedrose::{GildedRose, Item};
+
+        #[test]
+        fn given_new_item_then_holds_a_single_unit() {
+            assert_eq!(Item::new("Item", 5, 10).quantity, 1);
+        }
+
+        #[test]
+        fn given_stacks_when_totalled_then_scales_with_quantity() {
+            // given
+            let rose = GildedRose::new(vec![
+                Item::with_quantity("Item", 5, 10, 3),
+                Item::new("Elixir", 5, 20),
+            ]);
+
+            // when
+            let total = rose.total_quality();
+
+            // then
+            assert_eq!(total, 50);
+            assert_eq!(rose.average_quality(), Some(12.5));
+        }
+
+        #[test]
+        fn given_priced_stack_when_valued_then_scales_with_quantity() {
+            // given
+            let item = Item {
+                quantity: 4,
+                ..Item::with_price("Aged Brie", 2, 10, 3)
+            };
+            let rose = GildedRose::new(vec![item]);
+
+            // when
+            let value = rose.total_value();
+
+            // then
+            assert_eq!(value, 120);
+        }
+
+        #[test]
+        fn given_stack_when_updated_then_decays_once_per_stack() {
+            // given
+            let mut rose = GildedRose::new(vec![Item::with_quantity("Item", 5, 10, 3)]);
+
+            // when
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0], Item::with_quantity("Item", 4, 9, 3));
+        }
+    }
+
+    mod normalize {
+        use crate::gildedrose::{GildedRose, Item};
+
+        #[test]
+        fn given_dirty_items_when_normalized_then_brings_quality_into_bounds() {
+            // given
+            let mut rose = GildedRose::new(vec![
+                Item::new("Sulfuras, Hand of Ragnaros", 3, 10),
+                Item::new("Item", 5, 99),
+                Item::new("Aged Brie", 2, -4),
+                Item::new("Elixir", 5, 20),
+            ]);
+
+            // when
+            rose.normalize();
+
+            // then
+            let qualities: Vec<i32> = rose.iter().map(|item| item.quality).collect();
+            assert_eq!(qualities, vec![80, 50, 0, 20]);
+            assert_eq!(rose.items[0].sell_in, 3);
+            assert_eq!(rose.current_day(), 0);
+        }
+    }
+
+    mod hashing {
+        use crate::gildedrose::Item;
+        use std::collections::HashSet;
+
+        #[test]
+        fn given_identical_items_when_collected_into_set_then_keeps_one() {
+            // given
+            let mut set = HashSet::new();
+
+            // when
+            set.insert(Item::new("Aged Brie", 2, 10));
+            set.insert(Item::new("Aged Brie", 2, 10));
+
+            // then
+            assert_eq!(set.len(), 1);
+        }
+
+        #[test]
+        fn given_items_differing_in_quality_when_collected_into_set_then_keeps_both() {
+            let set: HashSet<Item> =
+                vec![Item::new("Aged Brie", 2, 10), Item::new("Aged Brie", 2, 11)]
+                    .into_iter()
+                    .collect();
+
+            assert_eq!(set.len(), 2);
+        }
+    }
+
+    mod appreciation_cap {
+        use crate::gildedrose::{GildedRose, Item, QualityConfig};
+
+        fn updated(item: Item, appreciation_cap: Option<i32>) -> Item {
+            let config = QualityConfig {
+                appreciation_cap,
+                ..QualityConfig::default()
+            };
+            let mut rose = GildedRose::with_config(vec![item], config);
+            rose.update_quality();
+            rose.items.remove(0)
+        }
+
+        #[test]
+        fn given_no_cap_when_expired_brie_updated_then_gains_two() {
+            assert_eq!(updated(Item::new("Aged Brie", 0, 10), None).quality, 12);
+        }
+
+        #[test]
+        fn given_cap_of_one_when_expired_brie_updated_then_gains_one() {
+            assert_eq!(updated(Item::new("Aged Brie", 0, 10), Some(1)).quality, 11);
+        }
+
+        #[test]
+        fn given_cap_of_one_when_backstage_pass_updated_then_gains_one() {
+            assert_eq!(
+                updated(Item::new("Backstage passes", 5, 10), None).quality,
+                13
+            );
+            assert_eq!(
+                updated(Item::new("Backstage passes", 5, 10), Some(1)).quality,
+                11
+            );
+        }
+
+        #[test]
+        fn given_cap_when_pass_expires_then_still_drops_to_zero() {
+            assert_eq!(
+                updated(Item::new("Backstage passes", 0, 10), Some(1)).quality,
+                0
+            );
+        }
+
+        #[test]
+        fn given_cap_when_normal_item_updated_then_decays_as_usual() {
+            assert_eq!(updated(Item::new("Item", 0, 10), Some(1)).quality, 8);
+        }
+    }
+
+    mod merge {
+        use crate::gildedrose::{GildedRose, Item};
+
+        fn warehouses() -> (GildedRose, GildedRose) {
+            (
+                GildedRose::new(vec![
+                    Item::with_quantity("Item", 5, 10, 2),
+                    Item::new("Aged Brie", 2, 10),
+                ]),
+                GildedRose::new(vec![
+                    Item::with_quantity("Item", 5, 10, 3),
+                    Item::new("Item", 4, 10),
+                ]),
+            )
+        }
+
+        #[test]
+        fn when_merged_then_appends_all_items() {
+            // given
+            let (mut rose, other) = warehouses();
+
+            // when
+            rose.merge(other);
+
+            // then
+            assert_eq!(
+                rose.items,
+                vec![
+                    Item::with_quantity("Item", 5, 10, 2),
+                    Item::new("Aged Brie", 2, 10),
+                    Item::with_quantity("Item", 5, 10, 3),
+                    Item::new("Item", 4, 10),
+                ]
+            );
+        }
+
+        #[test]
+        fn when_merged_stacking_then_sums_quantities_of_identical_items() {
+            // given
+            let (mut rose, other) = warehouses();
+
+            // when
+            rose.merge_stacking(other);
+
+            // then
+            assert_eq!(
+                rose.items,
+                vec![
+                    Item::with_quantity("Item", 5, 10, 5),
+                    Item::new("Aged Brie", 2, 10),
+                    Item::new("Item", 4, 10),
+                ]
+            );
+        }
+    }
+
+    mod appreciating {
+        use crate::gildedrose::{
+            CalculateQuality, CalculateSellIn, Calculations, GildedRose, Item, QualityConfig,
+        };
+
+        struct Wine;
+
+        impl CalculateQuality for Wine {
+            fn calculate_new_quality(&self, _: i32, quality: i32, _: &QualityConfig) -> i32 {
+                quality + 2
+            }
+        }
+
+        impl CalculateSellIn for Wine {}
+
+        impl Calculations for Wine {}
+
+        #[test]
+        fn given_mixed_inventory_when_listed_then_returns_items_gaining_quality() {
+            // given
+            let mut rose = GildedRose::new(vec![
+                Item::new("Aged Brie", 2, 10),
+                Item::new("Aged Brie", 2, 50),
+                Item::new("Backstage passes", 5, 20),
+                Item::new("Backstage passes", 0, 20),
+                Item::new("Sulfuras, Hand of Ragnaros", 0, 80),
+                Item::new("Item", 5, 20),
+                Item::new("Cheese wheel", 3, 20),
+                Item::new("Fine Wine", 3, 20),
+            ]);
+            rose.register_calculator(|name| name == "Fine Wine", || Box::new(Wine));
+
+            // when
+            let appreciating = rose.appreciating_items();
+
+            // then
+            assert_eq!(
+                appreciating,
+                vec![
+                    &Item::new("Aged Brie", 2, 10),
+                    &Item::new("Backstage passes", 5, 20),
+                    &Item::new("Cheese wheel", 3, 20),
+                    &Item::new("Fine Wine", 3, 20),
+                ]
+            );
+        }
+
+        #[test]
+        fn given_legendary_below_its_fixed_quality_when_listed_then_leaves_it_out() {
+            // given
+            let mut rose = GildedRose::new(vec![
+                Item::new("Sulfuras, Hand of Ragnaros", 0, 10),
+                Item::new("Excalibur", 0, 10),
+            ]);
+            rose.register_legendary("Excalibur", 50);
+
+            // when
+            let appreciating = rose.appreciating_items();
+
+            // then
+            assert!(appreciating.is_empty());
+        }
+    }
+
+    mod archived {
+        use crate::gildedrose::{GildedRose, Item};
+
+        fn rose() -> GildedRose {
+            GildedRose::new(vec![Item::new("Item", 5, 10), Item::new("Elixir", 5, 10)])
+        }
+
+        #[test]
+        fn given_archived_item_when_updated_then_does_not_decay() {
+            // given
+            let mut rose = rose();
+            rose.archive("Item");
+
+            // when
+            rose.advance_days(3);
+
+            // then
+            assert_eq!(rose.items[0].sell_in, 5);
+            assert_eq!(rose.items[0].quality, 10);
+            assert_eq!(rose.items[1], Item::new("Elixir", 2, 7));
+        }
+
+        #[test]
+        fn given_unarchived_item_when_updated_then_decays_again() {
+            // given
+            let mut rose = rose();
+            rose.archive("Item");
+            rose.update_quality();
+
+            // when
+            rose.unarchive("Item");
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0], Item::new("Item", 4, 9));
+        }
+
+        #[test]
+        fn given_archived_item_when_listed_then_splits_active_and_archived() {
+            // given
+            let mut rose = rose();
+
+            // when
+            rose.archive("Elixir");
+
+            // then
+            let names = |items: Vec<&Item>| -> Vec<String> {
+                items.iter().map(|item| item.name.clone()).collect()
+            };
+            assert_eq!(names(rose.active_items()), vec!["Item"]);
+            assert_eq!(names(rose.archived_items()), vec!["Elixir"]);
+        }
+
+        #[test]
+        fn given_unknown_name_when_archived_then_returns_false() {
+            assert!(!rose().archive("Vest"));
+        }
+    }
+
+    mod step {
+        use crate::gildedrose::{GildedRose, Item};
+
+        #[test]
+        fn given_only_sulfuras_when_stepped_then_reports_no_change() {
+            // given
+            let mut rose = GildedRose::new(vec![
+                Item::new("Sulfuras, Hand of Ragnaros", 0, 80),
+                Item::new("Sulfuras, Hand of Ragnaros", -1, 80),
+            ]);
+
+            // when
+            let changed = rose.step();
+
+            // then
+            assert!(!changed);
+            assert_eq!(rose.current_day(), 1);
+        }
+
+        #[test]
+        fn given_mixed_shop_when_stepped_then_reports_change() {
+            // given
+            let mut rose = GildedRose::new(vec![
+                Item::new("Sulfuras, Hand of Ragnaros", 0, 80),
+                Item::new("Item", 5, 0),
+            ]);
+
+            // when
+            let changed = rose.step();
+
+            // then
+            assert!(changed);
+        }
+    }
+
+    mod kind_tags {
+        use crate::gildedrose::{GildedRose, Item, ItemKind};
+
+        #[test]
+        fn given_tagged_names_when_classified_then_tag_wins() {
+            assert_eq!(
+                ItemKind::from_name("[legendary] Custom Sword"),
+                ItemKind::Sulfuras
+            );
+            assert_eq!(
+                ItemKind::from_name("[conjured] Aged Brie"),
+                ItemKind::Conjured
+            );
+            assert_eq!(ItemKind::from_name("[brie] Old Gouda"), ItemKind::AgedBrie);
+            assert_eq!(
+                ItemKind::from_name("[PASS] Opera night"),
+                ItemKind::BackstagePasses
+            );
+        }
+
+        #[test]
+        fn given_untagged_name_when_classified_then_falls_back_to_name() {
+            assert_eq!(ItemKind::from_name("Aged Brie"), ItemKind::AgedBrie);
+            assert_eq!(ItemKind::from_name("[sale] Aged Brie"), ItemKind::Normal);
+        }
+
+        #[test]
+        fn given_legendary_tag_when_updated_then_behaves_like_sulfuras() {
+            // given
+            let mut rose = GildedRose::new(vec![Item::new("[legendary] Custom Sword", 3, 80)]);
+
+            // when
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0], Item::new("[legendary] Custom Sword", 3, 80));
+        }
+
+        #[test]
+        fn given_tagged_item_when_displayed_then_hides_tag() {
+            // given
+            let item = Item::new("[legendary] Custom Sword", 3, 80);
+
+            // then
+            assert_eq!(item.display_name(), "Custom Sword");
+            assert_eq!(item.to_string(), "Custom Sword, 3, 80");
+            assert_eq!(
+                GildedRose::new(vec![item]).to_string(),
+                "Name          SellIn  Quality\n\
+                 Custom Sword       3       80\n"
+            );
+        }
+    }
+
+    mod clamped {
+        use crate::gildedrose::{GildedRose, Item};
+
+        fn clamped(item: Item) -> bool {
+            GildedRose::new(vec![item]).update_quality_with_report()[0].clamped
+        }
+
+        #[test]
+        fn given_aged_brie_at_50_when_updated_then_reports_clamp_at_cap() {
+            assert!(clamped(Item::new("Aged Brie", 5, 50)));
+        }
+
+        #[test]
+        fn given_normal_item_at_0_when_updated_then_reports_clamp_at_floor() {
+            assert!(clamped(Item::new("Item", 5, 0)));
+        }
+
+        #[test]
+        fn given_quality_within_bounds_when_updated_then_reports_no_clamp() {
+            assert!(!clamped(Item::new("Aged Brie", 5, 49)));
+            assert!(!clamped(Item::new("Item", 5, 1)));
+            assert!(!clamped(Item::new("Sulfuras, Hand of Ragnaros", 0, 80)));
+        }
+    }
+
+    mod fastest_declining {
+        use crate::gildedrose::{GildedRose, Item};
+
+        #[test]
+        fn given_expired_conjured_item_when_queried_then_beats_normal_items() {
+            // given
+            let rose = GildedRose::new(vec![
+                Item::new("Item", -1, 20),
+                Item::new("Conjured Mana Cake", -1, 20),
+                Item::new("Conjured Cookie", 5, 20),
+                Item::new("Aged Brie", -1, 20),
+            ]);
+
+            // when
+            let fastest = rose.fastest_declining();
+
+            // then
+            assert_eq!(fastest, Some(&Item::new("Conjured Mana Cake", -1, 20)));
+        }
+
+        #[test]
+        fn given_tied_deltas_when_queried_then_prefers_lowest_sell_in() {
+            // given
+            let rose = GildedRose::new(vec![
+                Item::new("Later", -1, 20),
+                Item::new("Sooner", -4, 20),
+                Item::new("Conjured Cookie", 5, 20),
+            ]);
+
+            // when
+            let fastest = rose.fastest_declining();
+
+            // then
+            assert_eq!(fastest.map(|item| item.name.as_str()), Some("Sooner"));
+        }
+
+        #[test]
+        fn given_nothing_declining_when_queried_then_is_none() {
+            let rose = GildedRose::new(vec![
+                Item::new("Sulfuras, Hand of Ragnaros", 0, 80),
+                Item::new("Aged Brie", 2, 10),
+                Item::new("Item", 5, 0),
+            ]);
+
+            assert_eq!(rose.fastest_declining(), None);
+        }
+    }
+
+    mod quality_floor {
+        use crate::gildedrose::{
+            CalculateQuality, CalculateSellIn, Calculations, DecayProfile, GildedRose, Item,
+            QualityConfig, QualityPolicy,
+        };
+
+        struct Acid;
+
+        impl CalculateQuality for Acid {
+            fn calculate_new_quality(&self, _: i32, quality: i32, _: &QualityConfig) -> i32 {
+                quality - 7
+            }
+        }
+
+        impl CalculateSellIn for Acid {}
+
+        impl Calculations for Acid {
+            fn clamp_quality(&self, raw: i32, _: &dyn QualityPolicy, _: &QualityConfig) -> i32 {
+                raw
+            }
+        }
+
+        fn items() -> Vec<Item> {
+            let names = [
+                "Item",
+                "Aged Brie",
+                "Backstage passes",
+                "Conjured Mana Cake",
+                "Cheese wheel",
+                "Acid",
+            ];
+            let mut items = Vec::new();
+            for name in names.iter() {
+                for sell_in in -3..=12 {
+                    for quality in 0..=50 {
+                        for &decay_multiplier in [0, 1, 3, 10].iter() {
+                            items.push(Item {
+                                decay_multiplier,
+                                ..Item::new(*name, sell_in, quality)
+                            });
+                        }
+                    }
+                }
+            }
+            items
+        }
+
+        #[test]
+        fn given_any_valid_item_when_updated_once_then_quality_is_not_negative() {
+            let profiles = [
+                DecayProfile::Linear,
+                DecayProfile::DoubleAfterExpiry,
+                DecayProfile::TripleAfterExpiry,
+                DecayProfile::Custom(|_| -100),
+            ];
+            for &decay_profile in profiles.iter() {
+                // given
+                let config = QualityConfig {
+                    decay_profile,
+                    ..QualityConfig::default()
+                };
+                let mut rose = GildedRose::with_config(items(), config);
+                rose.register_calculator(|name| name == "Acid", || Box::new(Acid));
+
+                // when
+                rose.update_quality();
+
+                // then
+                for item in rose.iter() {
+                    assert!(item.quality >= 0, "{}", item);
+                }
+            }
+        }
+    }
+
+    mod properties {
+        use crate::gildedrose::{GildedRose, Item};
+        use proptest::prelude::*;
+
+        fn normal_item() -> impl Strategy<Value = Item> {
+            (-100..100i32, 0..=50i32)
+                .prop_map(|(sell_in, quality)| Item::new("Item", sell_in, quality))
+        }
+
+        fn aged_brie() -> impl Strategy<Value = Item> {
+            (-100..100i32, 0..=50i32)
+                .prop_map(|(sell_in, quality)| Item::new("Aged Brie", sell_in, quality))
+        }
+
+        fn backstage_pass() -> impl Strategy<Value = Item> {
+            (-100..100i32, 0..=50i32)
+                .prop_map(|(sell_in, quality)| Item::new("Backstage passes", sell_in, quality))
+        }
+
+        fn conjured_item() -> impl Strategy<Value = Item> {
+            (-100..100i32, 0..=50i32)
+                .prop_map(|(sell_in, quality)| Item::new("Conjured Mana Cake", sell_in, quality))
+        }
+
+        fn sulfuras() -> impl Strategy<Value = Item> {
+            (-100..100i32).prop_map(|sell_in| Item::new("Sulfuras, Hand of Ragnaros", sell_in, 80))
+        }
+
+        fn non_legendary_item() -> impl Strategy<Value = Item> {
+            prop_oneof![
+                normal_item(),
+                aged_brie(),
+                backstage_pass(),
+                conjured_item()
+            ]
+        }
+
+        proptest! {
+            #[test]
+            fn non_legendary_quality_stays_within_bounds(
+                item in non_legendary_item(),
+                days in 0..60u32,
+            ) {
+                let mut rose = GildedRose::new(vec![item]);
+                for _ in 0..days {
+                    rose.update_quality();
+                    prop_assert!((0..=50).contains(&rose.items[0].quality));
+                }
+            }
+
+            #[test]
+            fn sulfuras_keeps_quality_and_sell_in(item in sulfuras(), days in 0..60u32) {
+                let mut rose = GildedRose::new(vec![item.clone()]);
+                rose.advance_days(days);
+                prop_assert_eq!(&rose.items[0], &item);
+            }
+
+            #[test]
+            fn expired_backstage_pass_is_worthless(
+                item in backstage_pass(),
+                days in 1..60u32,
+            ) {
+                let mut rose = GildedRose::new(vec![item]);
+                rose.advance_days(days);
+                if rose.items[0].sell_in < 0 {
+                    prop_assert_eq!(rose.items[0].quality, 0);
+                }
+            }
+
+            #[test]
+            fn sell_in_decreases_by_one_per_day(
+                item in non_legendary_item(),
+                days in 0..60u32,
+            ) {
+                let mut rose = GildedRose::new(vec![item.clone()]);
+                rose.advance_days(days);
+                prop_assert_eq!(rose.items[0].sell_in, item.sell_in - days as i32);
+            }
+        }
+    }
+
+    mod quality_after {
+        use crate::gildedrose::{GildedRose, Item, QualityConfig};
+
+        #[test]
+        fn given_normal_item_when_queried_then_accounts_for_faster_decay_after_expiry() {
+            // given
+            let rose = GildedRose::new(vec![Item::new("Item", 1, 20)]);
+
+            // when
+            let quality = rose.quality_after(0, 3);
+
+            // then
+            assert_eq!(quality, Some(15));
+            assert_eq!(rose.items[0].quality, 20);
+        }
+
+        #[test]
+        fn given_backstage_pass_when_queried_across_concert_then_drops_to_zero() {
+            // given
+            let rose = GildedRose::new(vec![Item::new("Backstage passes", 2, 20)]);
+
+            // then
+            assert_eq!(rose.quality_after(0, 2), Some(26));
+            assert_eq!(rose.quality_after(0, 3), Some(0));
+        }
+
+        #[test]
+        fn given_appreciation_cap_when_queried_then_applies_it() {
+            // given
+            let config = QualityConfig {
+                appreciation_cap: Some(1),
+                ..QualityConfig::default()
+            };
+            let rose = GildedRose::with_config(vec![Item::new("Aged Brie", 0, 10)], config);
+
+            // then
+            assert_eq!(rose.quality_after(0, 3), Some(13));
+        }
+
+        #[test]
+        fn given_index_out_of_range_when_queried_then_is_none() {
+            assert_eq!(GildedRose::new(vec![]).quality_after(0, 3), None);
+        }
+    }
+
+    mod rename_matching {
+        use crate::gildedrose::{GildedRose, Item};
+
+        #[test]
+        fn when_renamed_then_counts_renamed_items() {
+            // given
+            let mut rose = GildedRose::new(vec![
+                Item::new("Mana Cake", 5, 10),
+                Item::new("Item", 5, 10),
+                Item::new("Mana Potion", 5, 10),
+            ]);
+
+            // when
+            let renamed = rose
+                .rename_matching(|name| name.strip_prefix("Mana ").map(|rest| rest.to_string()));
+
+            // then
+            assert_eq!(renamed, 2);
+            let names: Vec<&str> = rose.iter().map(|item| item.name.as_str()).collect();
+            assert_eq!(names, vec!["Cake", "Item", "Potion"]);
+        }
+
+        #[test]
+        fn given_item_renamed_to_aged_brie_when_updated_then_ages_like_brie() {
+            // given
+            let mut rose = GildedRose::new(vec![Item::new("Item", 5, 10)]);
+            rose.rename_matching(|name| {
+                if name == "Item" {
+                    Some("Aged Brie".to_string())
+                } else {
+                    None
+                }
+            });
+
+            // when
+            rose.update_quality();
+
+            // then
+            assert_eq!(rose.items[0], Item::new("Aged Brie", 4, 11));
+        }
+    }
+
+    mod new_strict {
+        use crate::gildedrose::{GildedRose, Item, ItemKind, SuspiciousName};
+
+        fn suspicious(name: &str) -> Option<SuspiciousName> {
+            GildedRose::new_strict(vec![Item::new(name, 5, 10)]).err()
+        }
+
+        #[test]
+        fn given_sulfura_when_created_strictly_then_is_rejected() {
+            assert_eq!(
+                suspicious("Sulfura"),
+                Some(SuspiciousName {
+                    name: "Sulfura".to_string(),
+                    resembles: ItemKind::Sulfuras,
+                })
+            );
+        }
+
+        #[test]
+        fn given_aged_bri_when_created_strictly_then_is_rejected() {
+            assert_eq!(
+                suspicious("Aged Bri").map(|error| error.resembles),
+                Some(ItemKind::AgedBrie)
+            );
+        }
+
+        #[test]
+        fn given_typo_in_prefix_when_created_strictly_then_is_rejected() {
+            assert_eq!(
+                suspicious("Backstage pases to a TAFKAL80ETC concert").map(|error| error.resembles),
+                Some(ItemKind::BackstagePasses)
+            );
+        }
+
+        #[test]
+        fn given_regular_names_when_created_strictly_then_succeeds() {
+            // given
+            let items = vec![
+                Item::new("Sulfuras, Hand of Ragnaros", 0, 80),
+                Item::new("Aged Brie", 2, 10),
+                Item::new("Aged Brie Crumbs", 2, 10),
+                Item::new("+5 Dexterity Vest", 10, 20),
+                Item::new("Elixir of the Mongoose", 5, 7),
+            ];
+
+            // when
+            let rose = GildedRose::new_strict(items);
+
+            // then
+            assert!(rose.is_ok());
+        }
+    }
+
+    mod value_weighted_quality {
+        use crate::gildedrose::{GildedRose, Item};
+
+        #[test]
+        fn given_priced_items_when_weighted_then_favours_expensive_items() {
+            // given
+            let rose = GildedRose::new(vec![
+                Item::with_price("Cheap", 5, 10, 1),
+                Item::with_price("Pricey", 5, 40, 3),
+                Item::new("Unpriced", 5, 0),
+            ]);
+
+            // when
+            let weighted = rose.value_weighted_quality();
 
-impl BackstagePasses {
-    fn calculate_item_quality_increment(&self, sell_in: i32, quality: i32) -> i32 {
-        if sell_in < 11 && sell_in > 5 {
-            2
-        } else if sell_in <= 5 && sell_in > 0 {
-            3
-        } else if sell_in <= 0 {
-            -quality
-        } else {
-            1
+            // then
+            assert_eq!(weighted, Some(32.5));
         }
-    }
-}
 
-impl CalculateQuality for BackstagePasses {
-    fn calculate_new_quality(&self, sell_in: i32, quality: i32) -> i32 {
-        (quality + self.calculate_item_quality_increment(sell_in, quality)).min(50)
-    }
-}
+        #[test]
+        fn given_no_priced_items_when_weighted_then_is_none() {
+            let rose = GildedRose::new(vec![Item::new("Unpriced", 5, 10)]);
 
-impl CalculateSellIn for BackstagePasses {}
+            assert_eq!(rose.value_weighted_quality(), None);
+        }
+    }
 
-struct Sulfuras;
+    mod diff {
+        use crate::gildedrose::{GildedRose, Item, ItemDiff};
 
-impl CalculateQuality for Sulfuras {
-    fn calculate_new_quality(&self, _: i32, _: i32) -> i32 {
-        80
-    }
-}
+        #[test]
+        fn given_updated_copy_when_diffed_then_reports_changed_items() {
+            // given
+            let before = GildedRose::new(vec![
+                Item::new("Aged Brie", 2, 10),
+                Item::new("Sulfuras, Hand of Ragnaros", 0, 80),
+            ]);
+            let mut after = GildedRose::new(before.items.clone());
+            after.update_quality();
 
-impl CalculateSellIn for Sulfuras {
-    fn calculate_new_sell_in(&self, sell_in: i32) -> i32 {
-        sell_in
-    }
-}
+            // when
+            let diffs = before.diff(&after);
 
-struct DefaultItem;
+            // then
+            assert_eq!(
+                diffs,
+                vec![ItemDiff::Changed {
+                    name: "Aged Brie".to_string(),
+                    quality: (10, 11),
+                    sell_in: (2, 1),
+                }]
+            );
+        }
 
-impl DefaultItem {
-    fn calculate_item_quality_increment(&self, sell_in: i32) -> i32 {
-        DefaultQualityIncrement::get(sell_in)
-    }
-}
+        #[test]
+        fn given_items_in_one_shop_only_when_diffed_then_reports_added_and_removed() {
+            // given
+            let ours = GildedRose::new(vec![Item::new("Bread", 5, 5), Item::new("Milk", 3, 3)]);
+            let theirs = GildedRose::new(vec![Item::new("Milk", 3, 3), Item::new("Eggs", 7, 7)]);
 
-impl CalculateQuality for DefaultItem {
-    fn calculate_new_quality(&self, sell_in: i32, quality: i32) -> i32 {
-        (quality + self.calculate_item_quality_increment(sell_in)).max(0)
-    }
-}
+            // when
+            let diffs = ours.diff(&theirs);
 
-struct ConjuredItem;
+            // then
+            assert_eq!(
+                diffs,
+                vec![
+                    ItemDiff::Removed(Item::new("Bread", 5, 5)),
+                    ItemDiff::Added(Item::new("Eggs", 7, 7)),
+                ]
+            );
+        }
 
-impl ConjuredItem {
-    fn calculate_quality_increment(&self, sell_in: i32) -> i32 {
-        DefaultQualityIncrement::get(sell_in) * 2
-    }
-}
+        #[test]
+        fn given_identical_shops_when_diffed_then_is_empty() {
+            let rose = GildedRose::new(vec![Item::new("Bread", 5, 5)]);
 
-impl CalculateQuality for ConjuredItem {
-    fn calculate_new_quality(&self, sell_in: i32, quality: i32) -> i32 {
-        (quality + self.calculate_quality_increment(sell_in)).max(0)
+            assert!(rose.diff(&rose).is_empty());
+        }
     }
-}
 
-impl CalculateSellIn for ConjuredItem {}
-
-impl CalculateSellIn for DefaultItem {}
+    mod min_sell_in {
+        use crate::gildedrose::{GildedRose, Item, QualityConfig};
 
-struct CalculatorFactory;
+        fn updated_ten_times(item: Item) -> Item {
+            let config = QualityConfig {
+                min_sell_in: Some(-5),
+                ..QualityConfig::default()
+            };
+            let mut rose = GildedRose::with_config(vec![item], config);
+            rose.advance_days(10);
+            rose.items.remove(0)
+        }
 
-trait Calculations: CalculateQuality + CalculateSellIn {}
+        #[test]
+        fn given_floor_when_updated_past_it_then_sell_in_stops_at_floor() {
+            // given
+            let item = Item::new("Bread", 0, 40);
 
-impl Calculations for DefaultItem {}
+            // when
+            let item = updated_ten_times(item);
 
-impl Calculations for Sulfuras {}
+            // then
+            assert_eq!(item.sell_in, -5);
+            assert_eq!(item.quality, 20);
+        }
 
-impl Calculations for BackstagePasses {}
+        #[test]
+        fn given_floor_when_item_starts_below_it_then_sell_in_is_kept() {
+            let item = updated_ten_times(Item::new("Bread", -8, 40));
 
-impl Calculations for AgedBrie {}
+            assert_eq!(item.sell_in, -8);
+        }
 
-impl Calculations for ConjuredItem {}
+        #[test]
+        fn given_floor_when_legendary_below_it_then_sell_in_is_unchanged() {
+            let item = updated_ten_times(Item::new("Sulfuras, Hand of Ragnaros", -10, 80));
 
-impl CalculatorFactory {
-    fn create_calculator(item: &Item) -> Box<dyn Calculations> {
-        if item.name == "Aged Brie"
-        {
-            Box::new(AgedBrie)
-        } else if item.name.contains("Backstage passes") {
-            Box::new(BackstagePasses)
-        } else if item.name.contains("Sulfuras") {
-            Box::new(Sulfuras)
-        } else if item.name.contains("Conjured") {
-            Box::new(ConjuredItem)
-        } else {
-            Box::new(DefaultItem)
+            assert_eq!((item.sell_in, item.quality), (-10, 80));
         }
     }
-}
 
-pub struct GildedRose {
-    pub items: Vec<Item>,
-}
+    mod filter_clone {
+        use crate::gildedrose::{GildedRose, Item};
 
-impl GildedRose {
-    pub fn new(items: Vec<Item>) -> GildedRose {
-        GildedRose { items }
-    }
+        #[test]
+        fn given_mixed_quality_when_filter_cloned_then_keeps_valuable_items_only() {
+            // given
+            let rose = GildedRose::new(vec![
+                Item::new("Bread", 5, 10),
+                Item::new("Stale bread", -2, 0),
+                Item::new("Aged Brie", 2, 3),
+            ]);
 
-    pub fn update_quality(&mut self) {
-        for i in 0..self.items.len() {
-            self.items[i].quality = self.calculate_quality(&self.items[i]);
-            self.items[i].sell_in = self.calculate_sell_in(&self.items[i]);
+            // when
+            let valuable = rose.filter_clone(|item| item.quality > 0);
+
+            // then
+            assert_eq!(
+                valuable.items,
+                vec![Item::new("Bread", 5, 10), Item::new("Aged Brie", 2, 3)]
+            );
+            assert_eq!(rose.len(), 3);
         }
     }
 
-    fn calculate_sell_in(&self, item: &Item) -> i32 {
-        CalculatorFactory::create_calculator(&item)
-            .calculate_new_sell_in(item.sell_in)
-    }
+    #[cfg(feature = "log")]
+    mod logging {
+        use crate::gildedrose::{GildedRose, Item};
+        use log::{Level, Log, Metadata, Record};
+        use std::sync::{Mutex, Once};
 
-    fn calculate_quality(&self, item: &Item) -> i32 {
-        CalculatorFactory::create_calculator(&item)
-            .calculate_new_quality(item.sell_in, item.quality)
-    }
-}
+        struct CapturingLogger {
+            lines: Mutex<Vec<String>>,
+        }
 
-struct DefaultQualityIncrement;
+        impl Log for CapturingLogger {
+            fn enabled(&self, metadata: &Metadata) -> bool {
+                metadata.level() <= Level::Debug
+            }
 
-impl DefaultQualityIncrement {
-    fn get(sell_in: i32) -> i32 {
-        if sell_in < 1 {
-            -2
-        } else {
-            -1
+            fn log(&self, record: &Record) {
+                let line = record.args().to_string();
+                self.lines.lock().unwrap().push(line);
+            }
+
+            fn flush(&self) {}
         }
-    }
-}
 
-#[cfg(test)]
-mod tests {
-    mod conjured {
-        use crate::gildedrose::{GildedRose, Item};
+        static LOGGER: CapturingLogger = CapturingLogger {
+            lines: Mutex::new(Vec::new()),
+        };
+        static INIT: Once = Once::new();
+
+        /// Log lines mentioning `name`, since other tests may log concurrently.
+        fn lines_mentioning(name: &str) -> Vec<String> {
+            let lines = LOGGER.lines.lock().unwrap();
+            lines
+                .iter()
+                .filter(|line| line.contains(name))
+                .cloned()
+                .collect()
+        }
 
         #[test]
-        fn when_updated_then_decreases_in_quality_by_two() {
+        fn given_logger_when_updated_then_logs_one_line_per_item_per_update() {
             // given
-            let item = Item::new("Conjured Cookie", 20, 50);
-            let mut rose = GildedRose::new(vec![item]);
+            INIT.call_once(|| {
+                log::set_logger(&LOGGER).unwrap();
+                log::set_max_level(log::LevelFilter::Debug);
+            });
+            let mut rose = GildedRose::new(vec![
+                Item::new("[brie] Logged cheese", 2, 10),
+                Item::new("Logged bread", 5, 7),
+            ]);
 
             // when
             rose.update_quality();
+            rose.update_quality();
 
             // then
-            assert_eq!(rose.items[0].quality, 48);
+            assert_eq!(
+                lines_mentioning("\"[brie] Logged cheese\""),
+                vec![
+                    "updated \"[brie] Logged cheese\" with AgedBrie calculator: quality 10 -> 11, \
+                     sell_in 2 -> 1",
+                    "updated \"[brie] Logged cheese\" with AgedBrie calculator: quality 11 -> 12, \
+                     sell_in 1 -> 0",
+                ]
+            );
+            assert_eq!(lines_mentioning("\"Logged bread\"").len(), 2);
         }
+    }
+
+    mod is_stable {
+        use crate::gildedrose::{GildedRose, Item};
 
         #[test]
-        fn given_after_sale_when_updated_then_decreases_in_quality_by_four() {
+        fn given_only_sulfuras_when_checked_then_is_stable() {
+            let rose = GildedRose::new(vec![
+                Item::new("Sulfuras, Hand of Ragnaros", 0, 80),
+                Item::new("Sulfuras, Hand of Ragnaros", -1, 80),
+            ]);
+
+            assert!(rose.is_stable());
+        }
+
+        #[test]
+        fn given_frozen_items_when_checked_then_is_stable() {
             // given
-            let item = Item::new("Conjured Cookie", -4, 50);
-            let mut rose = GildedRose::new(vec![item]);
+            let mut bread = Item::new("Bread", 5, 10);
+            bread.freeze();
+            let mut brie = Item::new("Aged Brie", 2, 10);
+            brie.freeze();
 
             // when
-            rose.update_quality();
+            let rose = GildedRose::new(vec![bread, brie]);
 
             // then
-            assert_eq!(rose.items[0].quality, 46);
+            assert!(rose.is_stable());
         }
 
         #[test]
-        fn when_updated_then_sell_in_decreases() {
+        fn given_normal_item_when_checked_then_is_not_stable() {
+            let rose = GildedRose::new(vec![
+                Item::new("Sulfuras, Hand of Ragnaros", 0, 80),
+                Item::new("Item", 5, 0),
+            ]);
+
+            assert!(!rose.is_stable());
+        }
+    }
+
+    mod age {
+        use crate::gildedrose::Item;
+
+        #[test]
+        fn given_brie_when_aged_then_gains_quality() {
             // given
-            let item = Item::new("Conjured Cookie", -4, 50);
-            let mut rose = GildedRose::new(vec![item]);
+            let brie = Item::new("Aged Brie", 2, 10);
 
             // when
-            rose.update_quality();
+            let aged = brie.age(4);
 
             // then
-            assert_eq!(rose.items[0].sell_in, -5);
+            assert_eq!((aged.sell_in, aged.quality), (-2, 16));
+            assert_eq!(brie, Item::new("Aged Brie", 2, 10));
         }
 
         #[test]
-        fn given_zero_quality_when_updated_then_quality_is_zero() {
+        fn given_sulfuras_when_aged_then_is_unchanged() {
+            let sulfuras = Item::new("Sulfuras, Hand of Ragnaros", 0, 80);
+
+            assert_eq!(sulfuras.age(30), sulfuras);
+        }
+
+        #[test]
+        fn given_backstage_pass_when_aged_across_concert_then_drops_to_zero() {
             // given
-            let item = Item::new("Conjured", 2, 0);
-            let mut rose = GildedRose::new(vec![item]);
+            let pass = Item::new("Backstage passes to a TAFKAL80ETC concert", 3, 20);
 
             // when
-            rose.update_quality();
+            let on_concert_day = pass.age(3);
+            let after_concert = pass.age(4);
 
             // then
-            assert_eq!(rose.items[0].quality, 0);
+            assert_eq!((on_concert_day.sell_in, on_concert_day.quality), (0, 29));
+            assert_eq!((after_concert.sell_in, after_concert.quality), (-1, 0));
         }
     }
 
+    mod legendary_tolerance {
+        use crate::gildedrose::{GildedRose, Item, ItemError, LegendaryConfig, QualityConfig};
 
-    mod ragnaros {
-        use crate::gildedrose::{GildedRose, Item};
+        fn checked(quality: i32, tolerance: i32) -> (Result<(), Vec<ItemError>>, Item) {
+            let config = QualityConfig {
+                legendary: LegendaryConfig {
+                    tolerance,
+                    ..LegendaryConfig::default()
+                },
+                ..QualityConfig::default()
+            };
+            let sulfuras = Item::new("Sulfuras, Hand of Ragnaros", 0, quality);
+            let mut rose = GildedRose::with_config(vec![sulfuras], config);
+            let result = rose.update_quality_checked();
+            (result, rose.items.remove(0))
+        }
 
         #[test]
-        fn when_updated_then_does_not_alter_quality() {
-            // given
-            let item = Item::new("Sulfuras", 20, 80);
-            let mut rose = GildedRose::new(vec![item]);
+        fn given_no_tolerance_when_sulfuras_is_off_by_one_then_reports_error() {
+            // when
+            let (result, _) = checked(81, 0);
+
+            // then
+            assert_eq!(
+                result,
+                Err(vec![ItemError::InvalidLegendaryQuality {
+                    name: "Sulfuras, Hand of Ragnaros".to_string(),
+                    quality: 81,
+                }])
+            );
+        }
 
+        #[test]
+        fn given_tolerance_of_two_when_sulfuras_is_off_by_one_then_corrects_it() {
             // when
-            rose.update_quality();
+            let (result, sulfuras) = checked(81, 2);
 
             // then
-            assert_eq!(rose.items[0].quality, 80);
+            assert_eq!(result, Ok(()));
+            assert_eq!(sulfuras.quality, 80);
         }
 
         #[test]
-        fn when_updated_then_does_not_alter_sell_in() {
+        fn given_tolerance_of_two_when_sulfuras_is_off_by_three_then_reports_error() {
+            let (result, _) = checked(77, 2);
+
+            assert!(result.is_err());
+        }
+
+        #[test]
+        fn given_expected_quality_when_sulfuras_updated_then_holds_it() {
             // given
-            let item = Item::new("Sulfuras", 20, 80);
-            let mut rose = GildedRose::new(vec![item]);
+            let config = QualityConfig {
+                legendary: LegendaryConfig {
+                    expected_quality: 100,
+                    tolerance: 0,
+                },
+                ..QualityConfig::default()
+            };
+            let sulfuras = Item::new("Sulfuras, Hand of Ragnaros", 0, 100);
+            let mut rose = GildedRose::with_config(vec![sulfuras], config);
 
             // when
-            rose.update_quality();
+            let result = rose.update_quality_checked();
 
             // then
-            assert_eq!(rose.items[0].sell_in, 20);
+            assert_eq!(result, Ok(()));
+            assert_eq!(rose.items[0].quality, 100);
         }
     }
 
-    mod normal_item {
-        use crate::gildedrose::{GildedRose, Item};
+    mod summary {
+        use crate::gildedrose::{GildedRose, InventorySummary, Item};
 
         #[test]
-        fn when_updated_then_decreases_in_quality() {
+        fn given_mixed_inventory_when_summarized_then_matches_separate_aggregates() {
             // given
-            let item = Item::new("Item", 10, 50);
-            let mut rose = GildedRose::new(vec![item]);
+            let rose = GildedRose::new(vec![
+                Item::new("Sulfuras, Hand of Ragnaros", -1, 80),
+                Item::new("Bread", -2, 4),
+                Item::new("Aged Brie", 3, 10),
+                Item::new("Milk", 1, 6),
+            ]);
 
             // when
-            rose.update_quality();
+            let summary = rose.summary();
 
             // then
-            assert_eq!(rose.items[0].quality, 49);
+            assert_eq!(
+                summary,
+                InventorySummary {
+                    count: 4,
+                    expired: 1,
+                    total_quality: 100,
+                    average_quality: Some(25.0),
+                    legendary_count: 1,
+                }
+            );
+            assert_eq!(summary.expired, rose.count_expired());
+            assert_eq!(summary.total_quality, rose.total_quality());
+            assert_eq!(summary.average_quality, rose.average_quality());
         }
 
         #[test]
-        fn when_updated_then_sell_in_decreases() {
+        fn given_empty_shop_when_summarized_then_has_no_average() {
+            let summary = GildedRose::new(vec![]).summary();
+
+            assert_eq!(summary.count, 0);
+            assert_eq!(summary.average_quality, None);
+        }
+    }
+
+    mod registered_decay_profile {
+        use crate::gildedrose::{DecayProfile, GildedRose, Item, QualityConfig};
+
+        fn perishable_shop(items: Vec<Item>, config: QualityConfig) -> GildedRose {
+            let mut rose = GildedRose::with_config(items, config);
+            rose.register_decay_profile(
+                |name| name.starts_with("Perishable"),
+                DecayProfile::TripleAfterExpiry,
+            );
+            rose
+        }
+
+        #[test]
+        fn given_registered_profile_when_expired_item_matches_then_uses_it() {
             // given
-            let item = Item::new("Item", 10, 50);
-            let mut rose = GildedRose::new(vec![item]);
+            let mut rose = perishable_shop(
+                vec![
+                    Item::new("Perishable fish", 0, 20),
+                    Item::new("Bread", 0, 20),
+                ],
+                QualityConfig::default(),
+            );
 
             // when
             rose.update_quality();
 
             // then
-            assert_eq!(rose.items[0].sell_in, 9);
+            assert_eq!(rose.items[0].quality, 17);
+            assert_eq!(rose.items[1].quality, 18);
         }
 
         #[test]
-        fn given_zero_quality_when_updated_then_quality_is_zero() {
+        fn given_global_profile_when_item_matches_registration_then_registration_wins() {
             // given
-            let item = Item::new("Item", 10, 0);
-            let mut rose = GildedRose::new(vec![item]);
+            let config = QualityConfig {
+                decay_profile: DecayProfile::Linear,
+                ..QualityConfig::default()
+            };
+            let mut rose = perishable_shop(
+                vec![
+                    Item::new("Perishable fish", 0, 20),
+                    Item::new("Bread", 0, 20),
+                ],
+                config,
+            );
 
             // when
             rose.update_quality();
 
             // then
-            assert_eq!(rose.items[0].quality, 0);
+            assert_eq!(rose.items[0].quality, 17);
+            assert_eq!(rose.items[1].quality, 19);
         }
 
         #[test]
-        fn given_surpassed_sell_date_when_updated_quality_decreases_by_two() {
+        fn given_registered_profile_when_special_item_matches_then_is_unaffected() {
             // given
-            let item = Item::new("Item", 0, 4);
-            let mut rose = GildedRose::new(vec![item]);
+            let mut rose = GildedRose::new(vec![Item::new("Aged Brie", 0, 20)]);
+            rose.register_decay_profile(|_| true, DecayProfile::TripleAfterExpiry);
 
             // when
             rose.update_quality();
 
             // then
-            assert_eq!(rose.items[0].quality, 2);
+            assert_eq!(rose.items[0].quality, 22);
         }
     }
 
-    mod aged_brie {
+    mod depleted {
         use crate::gildedrose::{GildedRose, Item};
 
         #[test]
-        fn when_updated_then_sell_in_decreases() {
+        fn given_conjured_item_crossing_zero_when_updated_then_is_counted() {
             // given
-            let item = Item::new("Aged Brie", 2, 10);
-            let mut rose = GildedRose::new(vec![item]);
+            let mut rose = GildedRose::new(vec![
+                Item::new("Conjured Mana Cake", 3, 2),
+                Item::new("Bread", 3, 10),
+            ]);
 
             // when
-            rose.update_quality();
+            let depleted = rose.update_and_count_depleted();
 
             // then
-            assert_eq!(rose.items[0].sell_in, 1);
+            assert_eq!(depleted, 1);
+            assert_eq!(rose.items[0].quality, 0);
         }
 
         #[test]
-        fn when_updated_then_increases_in_quality() {
+        fn given_item_already_at_zero_when_updated_then_is_not_counted() {
+            let mut rose = GildedRose::new(vec![Item::new("Conjured Mana Cake", 3, 0)]);
+
+            assert_eq!(rose.update_and_count_depleted(), 0);
+        }
+
+        #[test]
+        fn given_legendary_fixed_at_zero_when_corrected_then_is_not_counted() {
             // given
-            let item = Item::new("Aged Brie", 2, 10);
-            let mut rose = GildedRose::new(vec![item]);
+            let mut rose = GildedRose::new(vec![Item::new("Void Stone", 0, 5)]);
+            rose.register_legendary("Void Stone", 0);
 
             // when
-            rose.update_quality();
+            let depleted = rose.update_and_count_depleted();
 
             // then
-            assert_eq!(rose.items[0].quality, 11);
+            assert_eq!(depleted, 0);
+            assert_eq!(rose.items[0].quality, 0);
+        }
+    }
+
+    mod item_quality_floor {
+        use crate::gildedrose::{GildedRose, Item};
+
+        fn updated(item: Item, days: u32) -> Item {
+            let mut rose = GildedRose::new(vec![item]);
+            rose.advance_days(days);
+            rose.items.remove(0)
         }
 
         #[test]
-        fn given_sell_in_date_when_updated_then_increases_in_quality_by_two() {
+        fn given_premium_item_when_expired_for_long_then_bottoms_out_at_floor() {
             // given
-            let item = Item::new("Aged Brie", 0, 10);
-            let mut rose = GildedRose::new(vec![item]);
+            let premium = Item::with_floor("Premium tea", 2, 20, 5);
 
             // when
-            rose.update_quality();
+            let premium = updated(premium, 20);
 
             // then
-            assert_eq!(rose.items[0].quality, 12);
+            assert_eq!(premium.quality, 5);
+            assert_eq!(updated(Item::new("Tea", 2, 20), 20).quality, 0);
         }
 
         #[test]
-        fn given_quality_of_50_when_updated_then_does_not_alter_quality() {
-            // given
-            let item = Item::new("Aged Brie", 20, 50);
-            let mut rose = GildedRose::new(vec![item]);
+        fn given_item_below_its_floor_when_updated_then_is_not_raised() {
+            let item = updated(Item::with_floor("Premium tea", 2, 3, 5), 1);
 
-            // when
-            rose.update_quality();
+            assert_eq!(item.quality, 3);
+        }
 
-            // then
-            assert_eq!(rose.items[0].quality, 50);
+        #[test]
+        fn given_legendary_with_floor_when_updated_then_is_unaffected() {
+            let sulfuras = Item::with_floor("Sulfuras, Hand of Ragnaros", 0, 80, 90);
+
+            assert_eq!(updated(sulfuras, 3).quality, 80);
         }
     }
 
-    mod backstage_passes {
-        use crate::gildedrose::{GildedRose, Item};
+    mod validate {
+        use crate::gildedrose::{GildedRose, Item, ItemError, QualityConfig, StandardPolicy};
 
         #[test]
-        fn when_updated_then_increases_in_quality() {
+        fn given_mixed_items_when_validated_then_reports_every_invalid_one() {
             // given
-            let item = Item::new("Backstage passes", 11, 10);
-            let mut rose = GildedRose::new(vec![item]);
+            let rose = GildedRose::new(vec![
+                Item::new("Bread", 5, 10),
+                Item::new("Sulfuras, Hand of Ragnaros", 0, 80),
+                Item::new("Sulfuras, Hand of Ragnaros", -3, 79),
+                Item::new("Aged Brie", 2, 51),
+                Item::new("Milk", 1, -1),
+            ]);
 
             // when
-            rose.update_quality();
+            let errors = rose.validate();
 
             // then
-            assert_eq!(rose.items[0].quality, 11);
+            assert_eq!(
+                errors,
+                vec![
+                    ItemError::InvalidLegendaryQuality {
+                        name: "Sulfuras, Hand of Ragnaros".to_string(),
+                        quality: 79,
+                    },
+                    ItemError::QualityTooHigh {
+                        name: "Aged Brie".to_string(),
+                        quality: 51,
+                        max: 50,
+                    },
+                    ItemError::QualityNegative {
+                        name: "Milk".to_string(),
+                        quality: -1,
+                        min: 0,
+                    },
+                ]
+            );
+            assert_eq!(rose.items[3].quality, 51);
         }
 
         #[test]
-        fn given_10_days_when_updated_then_increases_in_quality_by_two() {
+        fn given_registered_legendary_when_validated_then_checks_its_fixed_quality() {
             // given
-            let item = Item::new("Backstage passes", 10, 10);
-            let mut rose = GildedRose::new(vec![item]);
+            let mut rose = GildedRose::new(vec![
+                Item::new("Excalibur", 0, 100),
+                Item::new("Mjolnir", 0, 80),
+            ]);
+            rose.register_legendary("Excalibur", 100);
+            rose.register_legendary("Mjolnir", 90);
 
             // when
-            rose.update_quality();
+            let errors = rose.validate();
 
             // then
-            assert_eq!(rose.items[0].quality, 12);
+            assert_eq!(
+                errors,
+                vec![ItemError::InvalidLegendaryQuality {
+                    name: "Mjolnir".to_string(),
+                    quality: 80,
+                }]
+            );
         }
 
         #[test]
-        fn given_5_days_when_updated_then_increases_in_quality_by_three() {
+        fn given_policy_range_when_validated_then_reports_the_violated_bound() {
             // given
-            let item = Item::new("Backstage passes", 5, 10);
-            let mut rose = GildedRose::new(vec![item]);
+            let policy = StandardPolicy {
+                min_quality: -10,
+                max_quality: 40,
+            };
+            let items = vec![
+                Item::new("Debt", 5, -5),
+                Item::new("Deep debt", 5, -11),
+                Item::new("Shiny", 5, 45),
+            ];
+            let rose = GildedRose::with_policy(items, policy);
 
             // when
-            rose.update_quality();
+            let errors = rose.validate();
 
             // then
-            assert_eq!(rose.items[0].quality, 13);
+            assert_eq!(
+                errors,
+                vec![
+                    ItemError::QualityNegative {
+                        name: "Deep debt".to_string(),
+                        quality: -11,
+                        min: -10,
+                    },
+                    ItemError::QualityTooHigh {
+                        name: "Shiny".to_string(),
+                        quality: 45,
+                        max: 40,
+                    },
+                ]
+            );
+            assert_eq!(errors[1].to_string(), "quality 45 of Shiny is above 40");
         }
 
         #[test]
-        fn given_0_days_when_updated_quality_drops_to_zero() {
+        fn given_configured_max_when_validated_then_message_names_it() {
             // given
-            let item = Item::new("Backstage passes", 0, 10);
-            let mut rose = GildedRose::new(vec![item]);
+            let config = QualityConfig {
+                max_quality: 100,
+                ..QualityConfig::default()
+            };
+            let rose = GildedRose::with_config(vec![Item::new("X", 5, 120)], config);
 
             // when
+            let errors = rose.validate();
+
+            // then
+            assert_eq!(errors.len(), 1);
+            assert_eq!(errors[0].to_string(), "quality 120 of X is above 100");
+        }
+
+        #[test]
+        fn given_history_when_legendary_sell_in_edited_then_reports_it() {
+            // given
+            let mut rose = GildedRose::new(vec![
+                Item::new("Sulfuras, Hand of Ragnaros", 0, 80),
+                Item::new("Bread", 5, 10),
+            ]);
+            rose.enable_history();
             rose.update_quality();
+            assert!(rose.validate().is_empty());
+
+            // when
+            rose.items[0].sell_in = -1;
+            let errors = rose.validate();
 
             // then
-            assert_eq!(rose.items[0].quality, 0);
+            assert_eq!(
+                errors,
+                vec![ItemError::LegendarySellInChanged {
+                    name: "Sulfuras, Hand of Ragnaros".to_string(),
+                    sell_in: -1,
+                    expected: 0,
+                }]
+            );
+            assert_eq!(
+                errors[0].to_string(),
+                "legendary Sulfuras, Hand of Ragnaros has sell_in -1 instead of 0"
+            );
         }
 
         #[test]
-        fn when_updated_then_sell_in_decreases() {
+        fn given_checkpoint_when_legendary_sell_in_differs_then_reports_it() {
             // given
-            let item = Item::new("Backstage passes", 2, 0);
-            let mut rose = GildedRose::new(vec![item]);
+            let mut rose = GildedRose::new(vec![
+                Item::new("Sulfuras, Hand of Ragnaros", 3, 80),
+                Item::new("Sulfuras, Hand of Ragnaros", 7, 80),
+            ]);
+            let checkpoint = rose.checkpoint();
+            rose.advance_days(2);
+            rose.items[1].sell_in = 5;
 
             // when
-            rose.update_quality();
+            let errors = rose.validate_against(&checkpoint);
 
             // then
-            assert_eq!(rose.items[0].sell_in, 1);
+            assert_eq!(
+                errors,
+                vec![ItemError::LegendarySellInChanged {
+                    name: "Sulfuras, Hand of Ragnaros".to_string(),
+                    sell_in: 5,
+                    expected: 7,
+                }]
+            );
+        }
+
+        #[test]
+        fn given_valid_items_when_validated_then_is_empty() {
+            let rose = GildedRose::new(vec![
+                Item::new("Bread", 5, 0),
+                Item::new("Aged Brie", 2, 50),
+            ]);
+
+            assert!(rose.validate().is_empty());
         }
     }
 }
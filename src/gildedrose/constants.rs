@@ -0,0 +1,46 @@
+//! The numbers behind the Gilded Rose rules.
+
+/// Highest quality a non-legendary item can reach.
+pub const MAX_QUALITY: i32 = 50;
+
+/// Lowest quality a non-legendary item can drop to.
+pub const MIN_QUALITY: i32 = 0;
+
+/// Fixed quality of Sulfuras.
+pub const LEGENDARY_QUALITY: i32 = 80;
+
+/// Backstage passes gain 2 quality per day from this many days before the concert.
+pub const BACKSTAGE_TIER2_DAYS: i32 = 10;
+
+/// Backstage passes gain 3 quality per day from this many days before the concert.
+pub const BACKSTAGE_TIER3_DAYS: i32 = 5;
+
+/// Daily quality change of a normal item before its sell date.
+pub const QUALITY_INCREMENT: i32 = -1;
+
+/// Daily quality change of a normal item once its sell date has passed.
+pub const EXPIRED_QUALITY_INCREMENT: i32 = -2;
+
+/// Conjured items decay this many times faster than normal items.
+pub const CONJURED_DECAY_MULTIPLIER: i32 = 2;
+
+/// Cheese wheels ripen while their sell_in is above this day and spoil afterwards.
+pub const RIPENING_PEAK_DAY: i32 = 0;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn when_read_then_match_the_requirements() {
+        assert_eq!(MAX_QUALITY, 50);
+        assert_eq!(MIN_QUALITY, 0);
+        assert_eq!(LEGENDARY_QUALITY, 80);
+        assert_eq!(BACKSTAGE_TIER2_DAYS, 10);
+        assert_eq!(BACKSTAGE_TIER3_DAYS, 5);
+        assert_eq!(QUALITY_INCREMENT, -1);
+        assert_eq!(EXPIRED_QUALITY_INCREMENT, -2);
+        assert_eq!(CONJURED_DECAY_MULTIPLIER, 2);
+        assert_eq!(RIPENING_PEAK_DAY, 0);
+    }
+}
@@ -0,0 +1,414 @@
+use std::convert::TryFrom;
+use std::fmt::{self, Display};
+
+use super::{GildedRose, Item};
+
+const HEADER: &str = "name,sell_in,quality";
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum ParseItemError {
+    WrongFieldCount(usize),
+    InvalidInteger {
+        field: &'static str,
+        value: String,
+    },
+    /// A quoted field is not closed, or has text after its closing quote.
+    InvalidQuoting,
+}
+
+impl Display for ParseItemError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseItemError::WrongFieldCount(count) => {
+                write!(f, "expected 3 fields, found {}", count)
+            }
+            ParseItemError::InvalidInteger { field, value } => {
+                write!(f, "{} is not an integer: {:?}", field, value)
+            }
+            ParseItemError::InvalidQuoting => write!(f, "malformed quoted field"),
+        }
+    }
+}
+
+impl std::error::Error for ParseItemError {}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct CsvError {
+    pub line: usize,
+    pub kind: ParseItemError,
+}
+
+impl Display for CsvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.kind)
+    }
+}
+
+impl std::error::Error for CsvError {}
+
+fn parse_integer(field: &'static str, value: &str) -> Result<i32, ParseItemError> {
+    value.parse().map_err(|_| ParseItemError::InvalidInteger {
+        field,
+        value: value.to_string(),
+    })
+}
+
+/// Splits a line at commas, trimming whitespace around each field. A field wrapped in
+/// double quotes may contain commas, and `""` inside it stands for one quote.
+fn split_fields(line: &str) -> Result<Vec<String>, ParseItemError> {
+    let mut fields = Vec::new();
+    let mut rest = line;
+    loop {
+        let trimmed = rest.trim_start();
+        let (field, after) = match trimmed.strip_prefix('"') {
+            Some(quoted) => {
+                let (field, after) = split_quoted(quoted)?;
+                let after = after.trim_start();
+                if !after.is_empty() && !after.starts_with(',') {
+                    return Err(ParseItemError::InvalidQuoting);
+                }
+                (field, after)
+            }
+            None => {
+                let end = trimmed.find(',').unwrap_or(trimmed.len());
+                (trimmed[..end].trim_end().to_string(), &trimmed[end..])
+            }
+        };
+        fields.push(field);
+        match after.strip_prefix(',') {
+            Some(next) => rest = next,
+            None => return Ok(fields),
+        }
+    }
+}
+
+/// Reads a quoted field up to its closing quote, returning it and the text after.
+fn split_quoted(quoted: &str) -> Result<(String, &str), ParseItemError> {
+    let mut field = String::new();
+    let mut chars = quoted.char_indices();
+    while let Some((index, c)) = chars.next() {
+        if c != '"' {
+            field.push(c);
+        } else if quoted[index + 1..].starts_with('"') {
+            field.push('"');
+            chars.next();
+        } else {
+            return Ok((field, &quoted[index + 1..]));
+        }
+    }
+    Err(ParseItemError::InvalidQuoting)
+}
+
+/// Quotes `field` if it would otherwise not read back as written.
+fn escape_field(field: &str) -> String {
+    let needs_quotes = field.contains([',', '"']) || field.trim() != field;
+    if needs_quotes {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Parses a single `name,sell_in,quality` line, trimming whitespace around each field.
+///
+/// Names containing commas must be quoted, as in `"Sulfuras, Hand of Ragnaros",0,80`.
+impl TryFrom<&str> for Item {
+    type Error = ParseItemError;
+
+    fn try_from(line: &str) -> Result<Item, ParseItemError> {
+        let fields = split_fields(line)?;
+        if let [name, sell_in, quality] = &fields[..] {
+            Ok(Item::new(
+                name,
+                parse_integer("sell_in", sell_in)?,
+                parse_integer("quality", quality)?,
+            ))
+        } else {
+            Err(ParseItemError::WrongFieldCount(fields.len()))
+        }
+    }
+}
+
+fn is_header(line: &str) -> bool {
+    let fields: Vec<String> = line
+        .split(',')
+        .map(|field| field.trim().to_ascii_lowercase())
+        .collect();
+    fields.join(",") == HEADER
+}
+
+/// Parses every non-blank line after an optional header into an item or an error.
+fn parse_rows(input: &str) -> impl Iterator<Item = Result<Item, CsvError>> + '_ {
+    let mut seen_content = false;
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .filter(move |(_, line)| {
+            let first_content = !seen_content;
+            seen_content = true;
+            !(first_content && is_header(line))
+        })
+        .map(|(index, line)| {
+            Item::try_from(line).map_err(|kind| CsvError {
+                line: index + 1,
+                kind,
+            })
+        })
+}
+
+impl GildedRose {
+    /// Parses one `name,sell_in,quality` item per line, skipping blank lines and an
+    /// optional header. Names containing commas must be quoted.
+    pub fn from_csv(input: &str) -> Result<GildedRose, CsvError> {
+        parse_rows(input).collect()
+    }
+
+    /// Parses like [`GildedRose::from_csv`], but skips bad lines and returns their errors
+    /// alongside the shop built from the good ones.
+    pub fn from_csv_lossy(input: &str) -> (GildedRose, Vec<CsvError>) {
+        let mut items = Vec::new();
+        let mut errors = Vec::new();
+        for row in parse_rows(input) {
+            match row {
+                Ok(item) => items.push(item),
+                Err(error) => errors.push(error),
+            }
+        }
+        (GildedRose::new(items), errors)
+    }
+
+    /// Writes the items in the format [`GildedRose::from_csv`] reads, quoting names where
+    /// needed. Names containing line breaks do not read back.
+    pub fn to_csv(&self) -> String {
+        let mut csv = format!("{}\n", HEADER);
+        for item in &self.items {
+            csv.push_str(&format!(
+                "{},{},{}\n",
+                escape_field(&item.name),
+                item.sell_in,
+                item.quality
+            ));
+        }
+        csv
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use crate::gildedrose::{CsvError, GildedRose, Item, ParseItemError};
+
+    #[test]
+    fn given_valid_line_when_converted_then_creates_trimmed_item() {
+        assert_eq!(
+            Item::try_from("  Aged Brie , 10 ,20 "),
+            Ok(Item::new("Aged Brie", 10, 20))
+        );
+    }
+
+    #[test]
+    fn given_too_few_fields_when_converted_then_fails() {
+        assert_eq!(
+            Item::try_from("Aged Brie,10"),
+            Err(ParseItemError::WrongFieldCount(2))
+        );
+    }
+
+    #[test]
+    fn given_name_with_comma_when_converted_then_fails() {
+        assert_eq!(
+            Item::try_from("Sulfuras, Hand of Ragnaros,0,80"),
+            Err(ParseItemError::WrongFieldCount(4))
+        );
+    }
+
+    #[test]
+    fn given_quoted_name_with_comma_when_converted_then_keeps_comma() {
+        assert_eq!(
+            Item::try_from(" \"Sulfuras, Hand of Ragnaros\" ,0,80"),
+            Ok(Item::new("Sulfuras, Hand of Ragnaros", 0, 80))
+        );
+        assert_eq!(
+            Item::try_from("\"The \"\"Best\"\" Vest\",5,7"),
+            Ok(Item::new("The \"Best\" Vest", 5, 7))
+        );
+    }
+
+    #[test]
+    fn given_malformed_quotes_when_converted_then_fails() {
+        assert_eq!(
+            Item::try_from("\"Sulfuras, Hand of Ragnaros,0,80"),
+            Err(ParseItemError::InvalidQuoting)
+        );
+        assert_eq!(
+            Item::try_from("\"Sulfuras\" Hand,0,80"),
+            Err(ParseItemError::InvalidQuoting)
+        );
+    }
+
+    #[test]
+    fn given_non_numeric_sell_in_when_converted_then_fails() {
+        assert_eq!(
+            Item::try_from("Aged Brie,ten,20"),
+            Err(ParseItemError::InvalidInteger {
+                field: "sell_in",
+                value: "ten".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn given_lines_when_parsed_then_creates_items() {
+        // given
+        let input = "Aged Brie,10,20\n\nElixir of the Mongoose, 5, 7\n";
+
+        // when
+        let rose = GildedRose::from_csv(input).unwrap();
+
+        // then
+        assert_eq!(
+            rose.items,
+            vec![
+                Item::new("Aged Brie", 10, 20),
+                Item::new("Elixir of the Mongoose", 5, 7),
+            ]
+        );
+    }
+
+    #[test]
+    fn given_header_when_parsed_then_header_is_skipped() {
+        // given
+        let input = "Name, Sell_In, Quality\nAged Brie,10,20\n";
+
+        // when
+        let rose = GildedRose::from_csv(input).unwrap();
+
+        // then
+        assert_eq!(rose.items, vec![Item::new("Aged Brie", 10, 20)]);
+    }
+
+    #[test]
+    fn when_round_tripped_then_items_are_equal() {
+        // given
+        let rose = GildedRose::new(vec![
+            Item::new("+5 Dexterity Vest", 10, 20),
+            Item::new("Backstage passes to a TAFKAL80ETC concert", -1, 0),
+        ]);
+
+        // when
+        let restored = GildedRose::from_csv(&rose.to_csv()).unwrap();
+
+        // then
+        assert_eq!(restored.items, rose.items);
+    }
+
+    #[test]
+    fn given_names_needing_quotes_when_round_tripped_then_items_are_equal() {
+        // given
+        let rose = GildedRose::new(vec![
+            Item::new("Sulfuras, Hand of Ragnaros", 0, 80),
+            Item::new("The \"Best\" Vest", 5, 7),
+            Item::new(" Padded ", 3, 4),
+        ]);
+
+        // when
+        let csv = rose.to_csv();
+        let restored = GildedRose::from_csv(&csv).unwrap();
+
+        // then
+        assert!(csv.contains("\"Sulfuras, Hand of Ragnaros\",0,80\n"));
+        assert_eq!(restored.items, rose.items);
+    }
+
+    #[test]
+    fn when_written_then_emits_header_and_rows() {
+        // given
+        let rose = GildedRose::new(vec![Item::new("Aged Brie", 2, 0)]);
+
+        // when
+        let csv = rose.to_csv();
+
+        // then
+        assert_eq!(csv, "name,sell_in,quality\nAged Brie,2,0\n");
+    }
+
+    #[test]
+    fn given_malformed_integer_when_parsed_then_reports_line() {
+        // given
+        let input = "name,sell_in,quality\nAged Brie,10,20\n\nElixir,five,7\n";
+
+        // when
+        let error = GildedRose::from_csv(input).err().unwrap();
+
+        // then
+        assert_eq!(
+            error,
+            CsvError {
+                line: 4,
+                kind: ParseItemError::InvalidInteger {
+                    field: "sell_in",
+                    value: "five".to_string(),
+                },
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "line 4: sell_in is not an integer: \"five\""
+        );
+    }
+
+    #[test]
+    fn given_wrong_column_count_when_parsed_then_reports_line() {
+        // given
+        let input = "Aged Brie,10\n";
+
+        // when
+        let error = GildedRose::from_csv(input).err().unwrap();
+
+        // then
+        assert_eq!(
+            error,
+            CsvError {
+                line: 1,
+                kind: ParseItemError::WrongFieldCount(2),
+            }
+        );
+    }
+
+    #[test]
+    fn given_bad_lines_when_parsed_lossily_then_keeps_good_items_and_reports_rest() {
+        // given
+        let input = "name,sell_in,quality\n\
+                     Aged Brie,10,20\n\
+                     Elixir,five,7\n\
+                     Item,3,4\n\
+                     Vest,1\n";
+
+        // when
+        let (rose, errors) = GildedRose::from_csv_lossy(input);
+
+        // then
+        assert_eq!(
+            rose.items,
+            vec![Item::new("Aged Brie", 10, 20), Item::new("Item", 3, 4)]
+        );
+        assert_eq!(
+            errors,
+            vec![
+                CsvError {
+                    line: 3,
+                    kind: ParseItemError::InvalidInteger {
+                        field: "sell_in",
+                        value: "five".to_string(),
+                    },
+                },
+                CsvError {
+                    line: 5,
+                    kind: ParseItemError::WrongFieldCount(2),
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,69 @@
+use super::{GildedRose, Item};
+
+/// Renders a single item as one line of some text format.
+pub trait ItemFormat {
+    fn format_item(&self, item: &Item) -> String;
+}
+
+/// Formats items as `name|sell_in|quality`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PipeDelimited;
+
+impl ItemFormat for PipeDelimited {
+    fn format_item(&self, item: &Item) -> String {
+        format!("{}|{}|{}", item.name, item.sell_in, item.quality)
+    }
+}
+
+impl GildedRose {
+    /// Formats every item with `format`, one per line.
+    pub fn format_all<F: ItemFormat>(&self, format: &F) -> String {
+        self.items
+            .iter()
+            .map(|item| format!("{}\n", format.format_item(item)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gildedrose::{GildedRose, Item, ItemFormat, PipeDelimited};
+
+    fn rose() -> GildedRose {
+        GildedRose::new(vec![
+            Item::new("Aged Brie", 2, 10),
+            Item::new("Sulfuras, Hand of Ragnaros", 0, 80),
+        ])
+    }
+
+    #[test]
+    fn given_pipe_delimited_when_formatted_then_emits_one_line_per_item() {
+        assert_eq!(
+            rose().format_all(&PipeDelimited),
+            "Aged Brie|2|10\nSulfuras, Hand of Ragnaros|0|80\n"
+        );
+    }
+
+    #[test]
+    fn given_custom_format_when_formatted_then_uses_it() {
+        // given
+        struct FixedWidth;
+
+        impl ItemFormat for FixedWidth {
+            fn format_item(&self, item: &Item) -> String {
+                format!("{:<10.10}{:>4}{:>4}", item.name, item.sell_in, item.quality)
+            }
+        }
+
+        // when
+        let formatted = rose().format_all(&FixedWidth);
+
+        // then
+        assert_eq!(formatted, "Aged Brie    2  10\nSulfuras,    0  80\n");
+    }
+
+    #[test]
+    fn given_empty_shop_when_formatted_then_is_empty() {
+        assert_eq!(GildedRose::new(vec![]).format_all(&PipeDelimited), "");
+    }
+}
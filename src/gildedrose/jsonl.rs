@@ -0,0 +1,99 @@
+use std::fmt::{self, Display};
+use std::io::{self, BufRead};
+
+use super::{GildedRose, Item};
+
+#[derive(Debug)]
+pub enum ImportError {
+    Io(io::Error),
+    InvalidLine {
+        line: usize,
+        source: serde_json::Error,
+    },
+}
+
+impl Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportError::Io(error) => write!(f, "could not read input: {}", error),
+            ImportError::InvalidLine { line, source } => write!(f, "line {}: {}", line, source),
+        }
+    }
+}
+
+impl std::error::Error for ImportError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ImportError::Io(error) => Some(error),
+            ImportError::InvalidLine { source, .. } => Some(source),
+        }
+    }
+}
+
+impl From<io::Error> for ImportError {
+    fn from(error: io::Error) -> ImportError {
+        ImportError::Io(error)
+    }
+}
+
+impl GildedRose {
+    /// Reads one JSON item per line, skipping blank lines. Line numbers in errors are
+    /// 1-based.
+    pub fn from_jsonl<R: BufRead>(reader: R) -> Result<GildedRose, ImportError> {
+        let mut items = Vec::new();
+        for (index, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let item: Item =
+                serde_json::from_str(&line).map_err(|source| ImportError::InvalidLine {
+                    line: index + 1,
+                    source,
+                })?;
+            items.push(item);
+        }
+        Ok(GildedRose::new(items))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gildedrose::{GildedRose, ImportError, Item};
+
+    #[test]
+    fn given_json_lines_when_imported_then_reads_every_item() {
+        // given
+        let input = "{\"name\":\"Aged Brie\",\"sell_in\":2,\"quality\":10}\n\
+                     \n\
+                     {\"name\":\"Item\",\"sell_in\":5,\"quality\":7,\"price\":3}\n";
+
+        // when
+        let rose = GildedRose::from_jsonl(input.as_bytes());
+
+        // then
+        assert_eq!(
+            rose.map(|rose| rose.items).ok(),
+            Some(vec![
+                Item::new("Aged Brie", 2, 10),
+                Item::with_price("Item", 5, 7, 3)
+            ])
+        );
+    }
+
+    #[test]
+    fn given_malformed_line_when_imported_then_reports_its_line_number() {
+        // given
+        let input = "{\"name\":\"Aged Brie\",\"sell_in\":2,\"quality\":10}\n\
+                     {\"name\":\"Item\",\"sell_in\":\"soon\",\"quality\":7}\n";
+
+        // when
+        let result = GildedRose::from_jsonl(input.as_bytes());
+
+        // then
+        match result {
+            Err(ImportError::InvalidLine { line, .. }) => assert_eq!(line, 2),
+            _ => panic!("expected an invalid line error"),
+        }
+    }
+}
@@ -0,0 +1,174 @@
+//! A small DSL for setting up shops in tests.
+//!
+//! ```
+//! use rust::gildedrose::testing::{scenario, ShopAssertions};
+//!
+//! scenario()
+//!     .item("Aged Brie").sell_in(5).quality(10).add()
+//!     .build()
+//!     .advance(1)
+//!     .assert_quality("Aged Brie", 11)
+//!     .assert_sell_in("Aged Brie", 4);
+//! ```
+
+use super::{GildedRose, Item, ItemBuilder};
+
+pub fn scenario() -> Scenario {
+    Scenario::default()
+}
+
+/// Collects items for a [`GildedRose`] built with [`Scenario::build`].
+#[derive(Clone, Default, Debug)]
+pub struct Scenario {
+    items: Vec<Item>,
+}
+
+impl Scenario {
+    /// Starts an item named `name`, added to the scenario with [`ScenarioItem::add`].
+    pub fn item(self, name: impl Into<String>) -> ScenarioItem {
+        ScenarioItem {
+            scenario: self,
+            builder: ItemBuilder::new().name(name),
+        }
+    }
+
+    pub fn build(self) -> GildedRose {
+        GildedRose::new(self.items)
+    }
+}
+
+/// An item being described within a [`Scenario`], with the defaults of [`ItemBuilder`].
+#[derive(Clone, Debug)]
+pub struct ScenarioItem {
+    scenario: Scenario,
+    builder: ItemBuilder,
+}
+
+impl ScenarioItem {
+    pub fn sell_in(mut self, sell_in: i32) -> ScenarioItem {
+        self.builder = self.builder.sell_in(sell_in);
+        self
+    }
+
+    pub fn quality(mut self, quality: i32) -> ScenarioItem {
+        self.builder = self.builder.quality(quality);
+        self
+    }
+
+    pub fn add(mut self) -> Scenario {
+        self.scenario.items.push(self.builder.build());
+        self.scenario
+    }
+}
+
+/// Chainable updates and assertions on a shop. Assertions look at the first item with
+/// the given name and panic if there is none.
+pub trait ShopAssertions: Sized {
+    fn advance(self, days: u32) -> Self;
+    fn assert_quality(self, name: &str, quality: i32) -> Self;
+    fn assert_sell_in(self, name: &str, sell_in: i32) -> Self;
+}
+
+impl ShopAssertions for GildedRose {
+    fn advance(mut self, days: u32) -> GildedRose {
+        self.advance_days(days);
+        self
+    }
+
+    #[track_caller]
+    fn assert_quality(self, name: &str, quality: i32) -> GildedRose {
+        assert_eq!(
+            expect_item(&self, name).quality,
+            quality,
+            "quality of {}",
+            name
+        );
+        self
+    }
+
+    #[track_caller]
+    fn assert_sell_in(self, name: &str, sell_in: i32) -> GildedRose {
+        assert_eq!(
+            expect_item(&self, name).sell_in,
+            sell_in,
+            "sell_in of {}",
+            name
+        );
+        self
+    }
+}
+
+#[track_caller]
+fn expect_item<'a>(rose: &'a GildedRose, name: &str) -> &'a Item {
+    match rose.find_by_name(name) {
+        Some(item) => item,
+        None => panic!("no item named {:?}", name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gildedrose::testing::{scenario, ShopAssertions};
+    use crate::gildedrose::Item;
+
+    #[test]
+    fn given_scenario_when_built_then_holds_described_items() {
+        // given
+        let scenario = scenario()
+            .item("Aged Brie")
+            .sell_in(5)
+            .quality(10)
+            .add()
+            .item("Sulfuras, Hand of Ragnaros")
+            .add();
+
+        // when
+        let rose = scenario.build();
+
+        // then
+        assert_eq!(
+            rose.items,
+            vec![
+                Item::new("Aged Brie", 5, 10),
+                Item::new("Sulfuras, Hand of Ragnaros", 0, 80),
+            ]
+        );
+    }
+
+    #[test]
+    fn given_scenario_when_advanced_then_assertions_check_updated_items() {
+        scenario()
+            .item("Aged Brie")
+            .sell_in(1)
+            .quality(10)
+            .add()
+            .item("Backstage passes to a TAFKAL80ETC concert")
+            .sell_in(5)
+            .quality(20)
+            .add()
+            .build()
+            .advance(1)
+            .assert_quality("Aged Brie", 11)
+            .assert_sell_in("Aged Brie", 0)
+            .advance(1)
+            .assert_quality("Aged Brie", 13)
+            .assert_quality("Backstage passes to a TAFKAL80ETC concert", 26);
+    }
+
+    #[test]
+    #[should_panic(expected = "quality of Aged Brie")]
+    fn given_wrong_expectation_when_asserted_then_panics() {
+        scenario()
+            .item("Aged Brie")
+            .quality(10)
+            .add()
+            .build()
+            .assert_quality("Aged Brie", 11);
+    }
+
+    #[test]
+    #[should_panic(expected = "no item named \"Bread\"")]
+    fn given_missing_item_when_asserted_then_panics() {
+        scenario().build().assert_quality("Bread", 0);
+    }
+}
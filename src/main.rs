@@ -1,6 +1,4 @@
-mod gildedrose;
-
-use gildedrose::{GildedRose, Item};
+use rust::gildedrose::{GildedRose, Item};
 
 fn main() {
     let items = vec![
@@ -12,7 +10,6 @@ fn main() {
         Item::new("Backstage passes to a TAFKAL80ETC concert", 15, 20),
         Item::new("Backstage passes to a TAFKAL80ETC concert", 10, 49),
         Item::new("Backstage passes to a TAFKAL80ETC concert", 5, 49),
-        // this conjured item does not work properly yet
         Item::new("Conjured Mana Cake", 3, 6),
     ];
     let mut rose = GildedRose::new(items);